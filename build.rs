@@ -0,0 +1,18 @@
+//! Embeds the commit and build time served by `/version`.
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+    // Heroku builds from a tarball without `.git` and passes the commit here.
+    let sha = std::env::var("SOURCE_VERSION").ok().or_else(|| {
+        let output = Command::new("git").args(["rev-parse", "HEAD"]).output().ok()?;
+        output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    });
+    let built_at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    println!("cargo:rustc-env=GIT_SHA={}", sha.unwrap_or_else(|| "unknown".into()));
+    println!("cargo:rustc-env=BUILD_TIMESTAMP={built_at}");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-env-changed=SOURCE_VERSION");
+}
@@ -0,0 +1,12 @@
+-- CreateTable
+CREATE TABLE "SigningKey" (
+    "fingerprint" TEXT NOT NULL,
+    "public_key" BYTEA NOT NULL,
+    "user_id" BIGINT NOT NULL,
+    "created_at" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CONSTRAINT "SigningKey_pkey" PRIMARY KEY ("fingerprint")
+);
+
+-- AddForeignKey
+ALTER TABLE "SigningKey" ADD CONSTRAINT "SigningKey_user_id_fkey" FOREIGN KEY ("user_id") REFERENCES "User"("id") ON DELETE CASCADE ON UPDATE CASCADE;
@@ -0,0 +1,14 @@
+-- CreateTable
+CREATE TABLE "LogEntry" (
+    "index" BIGINT NOT NULL,
+    "kind" TEXT NOT NULL,
+    "data" TEXT NOT NULL,
+    "leaf_hash" BYTEA NOT NULL,
+    "created_at" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CONSTRAINT "LogEntry_pkey" PRIMARY KEY ("index")
+);
+
+-- Prevent the log from ever being rewritten
+CREATE RULE "LogEntry_no_update" AS ON UPDATE TO "LogEntry" DO INSTEAD NOTHING;
+CREATE RULE "LogEntry_no_delete" AS ON DELETE TO "LogEntry" DO INSTEAD NOTHING;
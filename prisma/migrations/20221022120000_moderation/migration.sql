@@ -0,0 +1,29 @@
+-- CreateEnum
+CREATE TYPE "Role" AS ENUM ('User', 'Moderator', 'Admin');
+
+-- AlterTable
+ALTER TABLE "User" ADD COLUMN "role" "Role" NOT NULL DEFAULT 'User',
+ADD COLUMN "banned_at" TIMESTAMP(3),
+ADD COLUMN "ban_reason" TEXT;
+
+-- CreateTable
+CREATE TABLE "LockedName" (
+    "name" TEXT NOT NULL,
+    "reason" TEXT NOT NULL,
+    "locked_by" BIGINT NOT NULL,
+    "created_at" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CONSTRAINT "LockedName_pkey" PRIMARY KEY ("name")
+);
+
+-- CreateTable
+CREATE TABLE "ModerationAction" (
+    "id" SERIAL NOT NULL,
+    "actor_id" BIGINT NOT NULL,
+    "action" TEXT NOT NULL,
+    "target" TEXT NOT NULL,
+    "reason" TEXT NOT NULL,
+    "created_at" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CONSTRAINT "ModerationAction_pkey" PRIMARY KEY ("id")
+);
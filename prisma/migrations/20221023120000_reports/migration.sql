@@ -0,0 +1,28 @@
+-- CreateEnum
+CREATE TYPE "ReportCategory" AS ENUM ('Malware', 'Spam', 'License', 'Impersonation');
+
+-- CreateEnum
+CREATE TYPE "ReportStatus" AS ENUM ('Open', 'Triaged', 'Resolved', 'Dismissed');
+
+-- CreateTable
+CREATE TABLE "Report" (
+    "id" SERIAL NOT NULL,
+    "reporter_id" BIGINT,
+    "plugin" TEXT NOT NULL,
+    "version" TEXT,
+    "category" "ReportCategory" NOT NULL,
+    "description" TEXT NOT NULL,
+    "status" "ReportStatus" NOT NULL DEFAULT 'Open',
+    "moderator_note" TEXT,
+    "handled_by" BIGINT,
+    "created_at" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "updated_at" TIMESTAMP(3) NOT NULL,
+
+    CONSTRAINT "Report_pkey" PRIMARY KEY ("id")
+);
+
+-- CreateIndex
+CREATE INDEX "Report_status_idx" ON "Report"("status");
+
+-- AddForeignKey
+ALTER TABLE "Report" ADD CONSTRAINT "Report_reporter_id_fkey" FOREIGN KEY ("reporter_id") REFERENCES "User"("id") ON DELETE SET NULL ON UPDATE CASCADE;
@@ -0,0 +1,35 @@
+-- Every existing user logged in with GitHub, and their id is their GitHub id.
+-- They keep that id, get a GitHub identity, and new users are numbered after
+-- the highest one. All of it in one transaction, so a failed upgrade leaves
+-- the users as they were.
+BEGIN;
+
+-- AlterTable
+CREATE SEQUENCE user_id_seq;
+ALTER TABLE "User" ALTER COLUMN "id" SET DEFAULT nextval('user_id_seq');
+ALTER SEQUENCE user_id_seq OWNED BY "User"."id";
+
+-- CreateTable
+CREATE TABLE "Identity" (
+    "id" TEXT NOT NULL,
+    "provider" TEXT NOT NULL,
+    "login" TEXT NOT NULL,
+    "user_id" BIGINT NOT NULL,
+    "created_at" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CONSTRAINT "Identity_pkey" PRIMARY KEY ("id")
+);
+
+-- CreateIndex
+CREATE INDEX "Identity_user_id_idx" ON "Identity"("user_id");
+
+-- AddForeignKey
+ALTER TABLE "Identity" ADD CONSTRAINT "Identity_user_id_fkey" FOREIGN KEY ("user_id") REFERENCES "User"("id") ON DELETE CASCADE ON UPDATE CASCADE;
+
+-- Link the existing users to their GitHub account
+INSERT INTO "Identity" ("id", "provider", "login", "user_id", "created_at")
+SELECT 'github:' || "id", 'github', "name", "id", "created_at" FROM "User";
+
+SELECT setval('user_id_seq', COALESCE(MAX("id"), 0) + 1, false) FROM "User";
+
+COMMIT;
@@ -0,0 +1,11 @@
+-- Names used to follow the GitHub login, so an account that was renamed on
+-- GitHub can still have a login that another user took since. The oldest
+-- account keeps the name, the others get their id appended.
+UPDATE "User" SET "name" = "name" || '-' || "id"
+WHERE "id" NOT IN (SELECT MIN("id") FROM "User" GROUP BY "name");
+
+-- DropIndex
+DROP INDEX "User_name_idx";
+
+-- CreateIndex
+CREATE UNIQUE INDEX "User_name_key" ON "User"("name");
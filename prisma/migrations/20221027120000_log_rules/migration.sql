@@ -0,0 +1,5 @@
+-- Databases set up with `db push` and then baselined never got the rules of
+-- the transparency_log migration. Replacing them adds them there, and leaves
+-- them as they were everywhere else.
+CREATE OR REPLACE RULE "LogEntry_no_update" AS ON UPDATE TO "LogEntry" DO INSTEAD NOTHING;
+CREATE OR REPLACE RULE "LogEntry_no_delete" AS ON DELETE TO "LogEntry" DO INSTEAD NOTHING;
@@ -23,7 +23,8 @@ fi
 echo "[INFO] Waiting 2s for the database to start"
 sleep 2s
 echo "[INFO] Running migrations"
-cargo prisma db push
+cargo prisma migrate deploy
+cargo prisma generate
 echo "[INFO] Setting shutdown trap"
 trap stop 1 3 9 2
 echo "[INFO] Starting backend"
@@ -0,0 +1,231 @@
+//! Portable registry archives, used by the `admin` binary for backups and to
+//! carry a registry into air-gapped environments.
+//!
+//! An archive is a gzipped tarball with one JSON file per table and a
+//! `manifest.json` listing the SHA-256 digest of each of them.
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use prisma_client_rust::Direction;
+use rocket::serde::json;
+use rocket::serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use crate::db;
+use crate::db::prisma::{identity, locked_name, log_entry, moderation_action, report, signing_key, user, PrismaClient};
+use crate::error::*;
+use crate::transparency;
+
+/// Changes whenever the files or their records change.
+pub const FORMAT_VERSION: u32 = 1;
+const MANIFEST: &str = "manifest.json";
+const USERS: &str = "users.json";
+const IDENTITIES: &str = "identities.json";
+const SIGNING_KEYS: &str = "signing_keys.json";
+const LOG_ENTRIES: &str = "log_entries.json";
+const LOCKED_NAMES: &str = "locked_names.json";
+const MODERATION_ACTIONS: &str = "moderation_actions.json";
+const REPORTS: &str = "reports.json";
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct Manifest {
+    pub format_version: u32,
+    pub created_at: u64,
+    pub files: Vec<ManifestFile>
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct ManifestFile {
+    pub path: String,
+    pub sha256: String,
+    pub records: usize
+}
+
+fn invalid_archive(message: impl Into<String>) -> Error {
+    Error::new(ErrorKind::ValidationError, "Check that the archive was produced by `admin export`", message)
+}
+
+fn io_error(err: std::io::Error) -> Error {
+    Error::new(ErrorKind::ValidationError, "Check the archive path and its permissions", err.to_string())
+}
+
+fn digest(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+fn encode<T: Serialize>(path: &str, records: &[T]) -> Result<(ManifestFile, Vec<u8>), Error> {
+    let data = json::to_string(records).map_err(|err| invalid_archive(err.to_string()))?.into_bytes();
+    Ok((ManifestFile { path: path.into(), sha256: digest(&data), records: records.len() }, data))
+}
+
+fn decode<T: for<'de> Deserialize<'de>>(files: &HashMap<String, Vec<u8>>, path: &str) -> Result<Vec<T>, Error> {
+    let data = files.get(path).ok_or_else(|| invalid_archive(format!("{path} is missing")))?;
+    let data = std::str::from_utf8(data).map_err(|err| invalid_archive(format!("{path}: {err}")))?;
+    json::from_str(data).map_err(|err| invalid_archive(format!("{path}: {err}")))
+}
+
+/// Writes every user, identity, signing key, transparency log entry,
+/// moderation record and abuse report to `path`.
+pub async fn export(client: &PrismaClient, path: &Path) -> Result<Manifest, Error> {
+    // Everything is read in one repeatable read transaction, so all tables
+    // come from the same snapshot and no row refers to one created or
+    // deleted while the export ran.
+    let (_, users, identities, keys, entries, names, actions, reports) = client._batch((
+        client._execute_raw(prisma_client_rust::raw!("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ READ ONLY")),
+        client.user().find_many(vec![]).order_by(user::id::order(Direction::Asc)),
+        client.identity().find_many(vec![]).order_by(identity::id::order(Direction::Asc)),
+        client.signing_key().find_many(vec![]).order_by(signing_key::fingerprint::order(Direction::Asc)),
+        client.log_entry().find_many(vec![]).order_by(log_entry::index::order(Direction::Asc)),
+        client.locked_name().find_many(vec![]).order_by(locked_name::name::order(Direction::Asc)),
+        client.moderation_action().find_many(vec![]).order_by(moderation_action::id::order(Direction::Asc)),
+        client.report().find_many(vec![]).order_by(report::id::order(Direction::Asc)),
+    ))
+        .await
+        .map_err(Error::database)?;
+
+    let files = vec![
+        encode(USERS, &users)?,
+        encode(IDENTITIES, &identities)?,
+        encode(SIGNING_KEYS, &keys)?,
+        encode(LOG_ENTRIES, &entries)?,
+        encode(LOCKED_NAMES, &names)?,
+        encode(MODERATION_ACTIONS, &actions)?,
+        encode(REPORTS, &reports)?,
+    ];
+    let created_at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let (files, contents): (Vec<_>, Vec<_>) = files.into_iter().unzip();
+    let manifest = Manifest { format_version: FORMAT_VERSION, created_at, files };
+    let manifest_data = json::to_pretty_string(&manifest).map_err(|err| invalid_archive(err.to_string()))?;
+
+    let mut archive = tar::Builder::new(GzEncoder::new(File::create(path).map_err(io_error)?, Compression::default()));
+    let mut append = |name: &str, data: &[u8]| {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(created_at);
+        header.set_cksum();
+        archive.append_data(&mut header, name, data).map_err(io_error)
+    };
+    append(MANIFEST, manifest_data.as_bytes())?;
+    for (file, data) in manifest.files.iter().zip(&contents) {
+        append(&file.path, data)?;
+    }
+    archive.into_inner().and_then(|gz| gz.finish()).map_err(io_error)?;
+    Ok(manifest)
+}
+
+/// Restores an archive into an empty database, after checking every file
+/// against the manifest and every log entry against its leaf hash.
+pub async fn import(client: &PrismaClient, path: &Path) -> Result<Manifest, Error> {
+    let mut archive = tar::Archive::new(GzDecoder::new(File::open(path).map_err(io_error)?));
+    let mut files = HashMap::new();
+    for entry in archive.entries().map_err(io_error)? {
+        let mut entry = entry.map_err(io_error)?;
+        let name = entry.path().map_err(io_error)?.to_string_lossy().into_owned();
+        let mut data = vec![];
+        entry.read_to_end(&mut data).map_err(io_error)?;
+        files.insert(name, data);
+    }
+
+    let manifest = files.get(MANIFEST).ok_or_else(|| invalid_archive("manifest.json is missing"))?;
+    let manifest: Manifest = json::from_str(std::str::from_utf8(manifest).map_err(|err| invalid_archive(err.to_string()))?)
+        .map_err(|err| invalid_archive(format!("manifest.json: {err}")))?;
+    if manifest.format_version != FORMAT_VERSION {
+        return Err(invalid_archive(format!("Unsupported archive format {}", manifest.format_version)));
+    }
+    for file in &manifest.files {
+        let data = files.get(&file.path).ok_or_else(|| invalid_archive(format!("{} is missing", file.path)))?;
+        if digest(data) != file.sha256 {
+            return Err(invalid_archive(format!("{} doesn't match its digest", file.path)));
+        }
+    }
+
+    let users: Vec<user::Data> = decode(&files, USERS)?;
+    let identities: Vec<identity::Data> = decode(&files, IDENTITIES)?;
+    let keys: Vec<signing_key::Data> = decode(&files, SIGNING_KEYS)?;
+    let entries: Vec<log_entry::Data> = decode(&files, LOG_ENTRIES)?;
+    let names: Vec<locked_name::Data> = decode(&files, LOCKED_NAMES)?;
+    let actions: Vec<moderation_action::Data> = decode(&files, MODERATION_ACTIONS)?;
+    let reports: Vec<report::Data> = decode(&files, REPORTS)?;
+    for (position, entry) in entries.iter().enumerate() {
+        if entry.index != position as i64 || transparency::leaf_hash(entry.data.as_bytes()).as_slice() != entry.leaf_hash {
+            return Err(invalid_archive(format!("Log entry {} is corrupted", entry.index)));
+        }
+    }
+
+    let existing_users = client.user().count(vec![]).exec().await.map_err(Error::database)?;
+    let existing_entries = client.log_entry().count(vec![]).exec().await.map_err(Error::database)?;
+    if existing_users > 0 || existing_entries > 0 {
+        return Err(Error::new(ErrorKind::ValidationError, "Import into a freshly migrated database", "The database isn't empty"));
+    }
+
+    // Rows keep their ids, which the audit log and reporters refer to, so
+    // the sequences have to start after them. Sequences ignore transactions,
+    // so moving them first is harmless if the import fails.
+    db::set_next_id(client, "User", users.iter().map(|user| user.id).max().unwrap_or(0) + 1).await?;
+    db::set_next_id(client, "ModerationAction", actions.iter().map(|action| action.id as i64).max().unwrap_or(0) + 1).await?;
+    db::set_next_id(client, "Report", reports.iter().map(|report| report.id as i64).max().unwrap_or(0) + 1).await?;
+
+    let identities: Vec<_> = identities.into_iter().map(|identity| client.identity()
+        .create(identity.id, identity.provider, identity.login, user::id::equals(identity.user_id), vec![
+            identity::created_at::set(identity.created_at),
+        ])
+    ).collect();
+    let users: Vec<_> = users.into_iter().map(|user| client.user()
+        .create(user.name, user.username, user.avatar_url, vec![
+            user::id::set(user.id),
+            user::role::set(user.role),
+            user::banned_at::set(user.banned_at),
+            user::ban_reason::set(user.ban_reason),
+            user::created_at::set(user.created_at),
+        ])
+    ).collect();
+    let keys: Vec<_> = keys.into_iter().map(|key| client.signing_key()
+        .create(key.fingerprint, key.public_key, user::id::equals(key.user_id), vec![
+            signing_key::created_at::set(key.created_at),
+        ])
+    ).collect();
+    let entries: Vec<_> = entries.into_iter().map(|entry| client.log_entry()
+        .create(entry.index, entry.kind, entry.data, entry.leaf_hash, vec![
+            log_entry::created_at::set(entry.created_at),
+        ])
+    ).collect();
+    let names: Vec<_> = names.into_iter().map(|name| client.locked_name()
+        .create(name.name, name.reason, name.locked_by, vec![
+            locked_name::created_at::set(name.created_at),
+        ])
+    ).collect();
+    let actions: Vec<_> = actions.into_iter().map(|action| client.moderation_action()
+        .create(action.actor_id, action.action, action.target, action.reason, vec![
+            moderation_action::id::set(action.id),
+            moderation_action::created_at::set(action.created_at),
+        ])
+    ).collect();
+    let reports: Vec<_> = reports.into_iter().map(|report| {
+        let mut params = vec![
+            report::id::set(report.id),
+            report::version::set(report.version),
+            report::status::set(report.status),
+            report::moderator_note::set(report.moderator_note),
+            report::handled_by::set(report.handled_by),
+            report::created_at::set(report.created_at),
+            report::updated_at::set(report.updated_at),
+        ];
+        if let Some(reporter_id) = report.reporter_id {
+            params.push(report::reporter::connect(user::id::equals(reporter_id)));
+        }
+        client.report().create(report.plugin, report.category, report.description, params)
+    }).collect();
+    // One batch is one transaction: a failure leaves the database empty, so
+    // the import can simply be run again.
+    client._batch((users, identities, keys, entries, names, actions, reports))
+        .await
+        .map_err(Error::database)?;
+    Ok(manifest)
+}
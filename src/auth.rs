@@ -0,0 +1,346 @@
+//! Logging in through OAuth identity providers.
+//!
+//! Each provider implements [`Provider`] and mounts a login and a callback
+//! route that hand over to [`login`] and [`callback`]. An account on a
+//! provider is stored as an `Identity` named `<provider>:<id on the provider>`,
+//! so ids of different providers never collide; `User.id` belongs to the
+//! registry and is what sessions, keys and reports refer to.
+//!
+//! A user can link identities of several providers and log in with any of
+//! them: `/login/<provider>?link=true` goes through the provider like a login,
+//! but the callback adds the identity to the logged in user.
+use prisma_client_rust::{raw, Direction, PrismaValue};
+use rocket::State;
+use rocket::fairing::AdHoc;
+use rocket::http::CookieJar;
+use rocket::response::Redirect;
+use rocket::serde::de::DeserializeOwned;
+use rocket::serde::json::Json;
+use rocket::serde::Serialize;
+use rocket_oauth2::{HyperRustlsAdapter, OAuth2, OAuthConfig, TokenResponse};
+use crate::{Session, SessionInfo};
+use crate::config::Config;
+use crate::db::prisma::{identity, user, PrismaClient};
+use crate::error::*;
+use crate::gitea::Gitea;
+use crate::github::GitHub;
+use crate::gitlab::GitLab;
+use crate::metrics::Metrics;
+use crate::moderation::Member;
+use crate::sessions::{self, Redis, UserAgent};
+
+/// A user as their provider describes them.
+pub struct Profile {
+    /// The provider's id for the user, only unique on that provider.
+    pub id: String,
+    pub login: String,
+    pub name: String,
+    pub avatar_url: String
+}
+
+#[rocket::async_trait]
+pub trait Provider: Send + Sync + 'static {
+    /// Prefix of the provider's identities, and last segment of its routes.
+    const NAME: &'static str;
+    const SCOPES: &'static [&'static str];
+
+    /// Name shown to users.
+    fn title(config: &Config) -> String;
+
+    /// The OAuth app, `None` when the provider isn't configured.
+    fn oauth_config(config: &Config) -> Option<OAuthConfig>;
+
+    /// The user `token` was issued to.
+    async fn profile(config: &Config, token: &str) -> Result<Profile, String>;
+
+    /// Revokes `token`, when the provider has an API for it. This happens on
+    /// every login, so the user's authorization of our OAuth app must stay,
+    /// or they'd be asked for it each time.
+    async fn revoke(_config: &Config, _token: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Kind of the error returned when [`Provider::profile`] fails.
+    fn api_error() -> ErrorKind {
+        ErrorKind::ProviderApiError
+    }
+}
+
+/// Registers the OAuth app of `P` at ignition, if it's configured.
+pub fn fairing<P: Provider>() -> AdHoc {
+    AdHoc::try_on_ignite(P::NAME, |rocket| async move {
+        let config = match rocket.state::<Config>() {
+            Some(config) => P::oauth_config(config),
+            None => return Err(rocket)
+        };
+        Ok(match config {
+            Some(config) => rocket.attach(OAuth2::<P>::custom(HyperRustlsAdapter::default(), config)),
+            None => rocket
+        })
+    })
+}
+
+/// GETs `url` with `token` as a bearer token and parses the JSON answer.
+pub async fn fetch_json<T: DeserializeOwned>(url: &str, token: &str) -> Result<T, String> {
+    reqwest::Client::new()
+        .get(url)
+        .bearer_auth(token)
+        .header("User-Agent", "LapceExtensions")
+        .send().await
+        .and_then(|response| response.error_for_status())
+        .map_err(|err| err.to_string())?
+        .json().await
+        .map_err(|err| err.to_string())
+}
+
+fn session_error(err: impl ToString) -> Error {
+    Error::new(ErrorKind::DatabaseError(err.to_string()), "Try again", "Can't set session on redis db")
+}
+
+/// Redirects to the provider, or fails if it isn't configured. With `link`,
+/// the logged in user is marked as linking an identity of `P`, which the
+/// callback then adds to them instead of logging in. Without it, a link the
+/// user started and abandoned is forgotten so this login doesn't link.
+pub async fn login<P: Provider>(oauth2: Option<OAuth2<P>>, cookies: &CookieJar<'_>, config: &Config, session: Session<'_>, link: bool) -> Result<Redirect, Error> {
+    let title = P::title(config);
+    let oauth2 = oauth2.ok_or_else(|| Error::new(
+        ErrorKind::NotFound,
+        "Log in with another provider",
+        format!("{title} login isn't enabled on this registry")
+    ))?;
+    if link {
+        let info = session.get().await.ok().flatten().ok_or_else(Error::not_logged_in)?;
+        session.set(SessionInfo { linking: Some(P::NAME.into()), ..info }).await.map_err(session_error)?;
+    } else if let Some(info) = session.get().await.ok().flatten().filter(|info| info.linking.is_some()) {
+        session.set(SessionInfo { linking: None, ..info }).await.map_err(session_error)?;
+    }
+    oauth2.get_redirect(cookies, P::SCOPES)
+        .map_err(|err| Error::new(ErrorKind::Unavailable, "Try again later", format!("Can't redirect to {title}: {err}")))
+}
+
+/// A name for a new user whose login on `provider` is `login`: the login
+/// itself if it's free, else followed by the provider and then a number.
+/// Names belong to the registry, so no login on any provider can take over
+/// one that's already used.
+async fn free_name(client: &PrismaClient, provider: &str, login: &str) -> Result<String, Error> {
+    for n in 0.. {
+        let name = match n {
+            0 => login.to_string(),
+            1 => format!("{login}-{provider}"),
+            n => format!("{login}-{provider}-{n}")
+        };
+        let taken = client.user()
+            .find_unique(user::name::equals(name.clone()))
+            .exec().await
+            .map_err(Error::database)?;
+        if taken.is_none() {
+            return Ok(name);
+        }
+    }
+    unreachable!()
+}
+
+/// The user `profile` logs into, created on its first login. Their display
+/// name and avatar follow the provider's on every login, their name stays.
+pub async fn sign_in(client: &PrismaClient, provider: &str, profile: &Profile) -> Result<user::Data, Error> {
+    let identity_id = format!("{provider}:{}", profile.id);
+    let existing = client.identity()
+        .find_unique(identity::id::equals(identity_id.clone()))
+        .exec().await
+        .map_err(Error::database)?;
+    match existing {
+        Some(identity) => {
+            client.identity()
+                .update(identity::id::equals(identity_id), vec![identity::login::set(profile.login.clone())])
+                .exec().await
+                .map_err(Error::database)?;
+            client.user()
+                .update(user::id::equals(identity.user_id), vec![
+                    user::username::set(profile.name.clone()),
+                    user::avatar_url::set(profile.avatar_url.clone()),
+                ])
+                .exec().await
+                .map_err(Error::database)
+        }
+        None => {
+            let name = free_name(client, provider, &profile.login).await?;
+            let user = client.user()
+                .create(name, profile.name.clone(), profile.avatar_url.clone(), vec![])
+                .exec().await
+                .map_err(Error::database)?;
+            let linked = client.identity()
+                .create(identity_id, provider.to_string(), profile.login.clone(), user::id::equals(user.id), vec![])
+                .exec().await;
+            if let Err(err) = linked {
+                // Another login of the same identity won the race.
+                client.user().delete(user::id::equals(user.id)).exec().await.ok();
+                return Err(Error::database(err));
+            }
+            Ok(user)
+        }
+    }
+}
+
+/// Adds the identity of `profile` to `user_id`. Refuses an identity that
+/// already logs into another user, as the two accounts would have to be
+/// merged.
+pub async fn link(client: &PrismaClient, provider: &str, profile: &Profile, user_id: i64) -> Result<(), Error> {
+    let identity_id = format!("{provider}:{}", profile.id);
+    let existing = client.identity()
+        .find_unique(identity::id::equals(identity_id.clone()))
+        .exec().await
+        .map_err(Error::database)?;
+    match existing {
+        Some(identity) if identity.user_id == user_id => Ok(()),
+        Some(_) => Err(Error::new(
+            ErrorKind::Conflict,
+            "Log in with that account and unlink it or delete its registry account first",
+            "This account is already linked to another registry account"
+        )),
+        None => client.identity()
+            .create(identity_id, provider.to_string(), profile.login.clone(), user::id::equals(user_id), vec![])
+            .exec().await
+            .map(|_| ())
+            .map_err(Error::database)
+    }
+}
+
+/// Opens a session for `user` unless they're banned.
+pub async fn start_session(session: Session<'_>, redis: &Redis, user_agent: UserAgent, metrics: &Metrics, user: user::Data, provider: &str) -> Result<Redirect, Error> {
+    if user.banned_at.is_some() {
+        let reason = user.ban_reason.unwrap_or_default();
+        return Err(Error::new(ErrorKind::Forbidden, "Contact the registry moderators", format!("This account is banned: {reason}")));
+    }
+    let sid = sessions::new_session_id();
+    session.set(SessionInfo {
+        id: user.id as u64,
+        provider: provider.into(),
+        sid: sid.clone(),
+        linking: None
+    }).await.map_err(session_error)?;
+    sessions::register(redis, user.id as u64, &sid, user_agent.0).await.map_err(session_error)?;
+    metrics.logins.inc();
+    Ok(Redirect::to("/"))
+}
+
+/// Finishes the OAuth flow of `P` and logs the user in, or links the identity
+/// if the session asked for it. The token is only used to fetch the profile,
+/// and revoked right after.
+pub async fn callback<P: Provider>(token: TokenResponse<P>, session: Session<'_>, redis: &Redis, user_agent: UserAgent, client: &PrismaClient, metrics: &Metrics, config: &Config) -> Result<Redirect, Error> {
+    let token = token.access_token();
+    let profile = P::profile(config, token).await;
+    // A failed revocation is only logged, the token expires eventually.
+    if let Err(err) = P::revoke(config, token).await {
+        warn!("Can't revoke the {} token: {}", P::NAME, err);
+        metrics.provider_api_failures.with_label_values(&[P::NAME]).inc();
+    }
+    let profile = profile.map_err(|err| {
+        let title = P::title(config);
+        warn!("{} API call failed: {}", title, err);
+        metrics.provider_api_failures.with_label_values(&[P::NAME]).inc();
+        Error::new(P::api_error(), "Try again", format!("Can't fetch your profile from {title}"))
+    })?;
+    let current = session.get().await.ok().flatten();
+    if let Some(info) = current.filter(|info| info.linking.as_deref() == Some(P::NAME)) {
+        link(client, P::NAME, &profile, info.id as i64).await?;
+        session.set(SessionInfo { linking: None, ..info }).await.map_err(session_error)?;
+        return Ok(Redirect::to("/"));
+    }
+    let user = sign_in(client, P::NAME, &profile).await?;
+    start_session(session, redis, user_agent, metrics, user, P::NAME).await
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ProviderInfo {
+    pub name: &'static str,
+    pub title: String,
+    pub login_url: String
+}
+
+fn info<P: Provider>(config: &Config) -> Option<ProviderInfo> {
+    P::oauth_config(config).map(|_| ProviderInfo {
+        name: P::NAME,
+        title: P::title(config),
+        login_url: format!("/login/{}", P::NAME)
+    })
+}
+
+/// Providers users can log in with on this registry.
+#[get("/auth/providers")]
+pub fn providers(config: &State<Config>) -> Json<Vec<ProviderInfo>> {
+    let providers = [info::<GitHub>(config), info::<GitLab>(config), info::<Gitea>(config), crate::dev::info(config)];
+    Json(providers.into_iter().flatten().collect())
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct IdentityInfo {
+    /// `<provider>:<id on the provider>`
+    pub id: String,
+    pub provider: String,
+    pub login: String,
+    pub created_at: String
+}
+
+impl From<identity::Data> for IdentityInfo {
+    fn from(identity: identity::Data) -> Self {
+        Self {
+            id: identity.id,
+            provider: identity.provider,
+            login: identity.login,
+            created_at: identity.created_at.to_rfc3339()
+        }
+    }
+}
+
+#[get("/user/identities")]
+pub async fn list_identities(member: Result<Member, Error>, client: &State<PrismaClient>) -> Result<Json<Vec<IdentityInfo>>, Error> {
+    let Member(user) = member?;
+    let identities = client.identity()
+        .find_many(vec![identity::user_id::equals(user.id)])
+        .order_by(identity::created_at::order(Direction::Asc))
+        .exec().await
+        .map_err(Error::database)?;
+    Ok(Json(identities.into_iter().map(IdentityInfo::from).collect()))
+}
+
+/// Unlinks an identity from the logged in user, who must keep at least one
+/// to log in with.
+#[delete("/user/identities/<id>")]
+pub async fn unlink_identity(member: Result<Member, Error>, id: &str, client: &State<PrismaClient>) -> Result<(), Error> {
+    let Member(user) = member?;
+    let identities = client.identity()
+        .find_many(vec![identity::user_id::equals(user.id)])
+        .exec().await
+        .map_err(Error::database)?;
+    if !identities.iter().any(|identity| identity.id == id) {
+        return Err(Error::new(ErrorKind::NotFound, "Check the identity id", "This identity isn't linked to your account"));
+    }
+    let only_identity = Error::new(
+        ErrorKind::Conflict,
+        "Link another identity first, or delete the account",
+        "You can't unlink the only identity you can log in with"
+    );
+    if identities.len() == 1 {
+        return Err(only_identity);
+    }
+    // Two unlinks at once could each see the other identity and remove both,
+    // so lock the user and count again right before deleting.
+    let (_, deleted) = client._batch((
+        client._execute_raw(raw!(
+            r#"SELECT id FROM "User" WHERE id = {} FOR UPDATE"#,
+            PrismaValue::BigInt(user.id)
+        )),
+        client._execute_raw(raw!(
+            r#"DELETE FROM "Identity" WHERE id = {} AND user_id = {} AND (SELECT count(*) FROM "Identity" WHERE user_id = {}) > 1"#,
+            PrismaValue::String(id.to_string()),
+            PrismaValue::BigInt(user.id),
+            PrismaValue::BigInt(user.id)
+        )),
+    )).await.map_err(Error::database)?;
+    if deleted == 0 {
+        return Err(only_identity);
+    }
+    Ok(())
+}
@@ -0,0 +1,63 @@
+//! Registry administration commands.
+//!
+//! ```text
+//! admin export <archive.tar.gz>      Write the whole registry to an archive
+//! admin import <archive.tar.gz>      Restore an archive into an empty database
+//! admin set-role <user id> <role>    Make a user a User, Moderator or Admin
+//! ```
+use std::path::Path;
+use std::process::exit;
+use dotenvy::dotenv;
+use server::archive::{self, Manifest};
+use server::config;
+use server::db::establish_connection;
+use server::db::prisma::{user, Role};
+
+fn usage() -> ! {
+    eprintln!("Usage: admin <export|import> <archive.tar.gz>");
+    eprintln!("       admin set-role <user id> <User|Moderator|Admin>");
+    exit(2)
+}
+
+fn print_manifest(manifest: Manifest) {
+    for file in manifest.files {
+        println!("{} {} ({} records)", file.sha256, file.path, file.records);
+    }
+}
+
+#[rocket::main]
+async fn main() {
+    dotenv().ok();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let url: String = config::figment().extract_inner("database_url").unwrap_or_else(|err| {
+        eprintln!("DATABASE_URL: {err}");
+        exit(1)
+    });
+    let client = establish_connection(&url).await.unwrap_or_else(|err| {
+        eprintln!("{err}");
+        exit(1)
+    });
+    let result: Result<(), String> = match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["export", path] => archive::export(&client, Path::new(path)).await.map(print_manifest).map_err(|err| err.to_string()),
+        ["import", path] => archive::import(&client, Path::new(path)).await.map(print_manifest).map_err(|err| err.to_string()),
+        ["set-role", id, role] => {
+            let id: i64 = id.parse().unwrap_or_else(|_| usage());
+            let role = match *role {
+                "User" => Role::User,
+                "Moderator" => Role::Moderator,
+                "Admin" => Role::Admin,
+                _ => usage()
+            };
+            client.user()
+                .update(user::id::equals(id), vec![user::role::set(role)])
+                .exec().await
+                .map(|user| println!("{} is now {:?}", user.name, user.role))
+                .map_err(|err| err.to_string())
+        }
+        _ => usage()
+    };
+    if let Err(err) = result {
+        eprintln!("{err}");
+        exit(1)
+    }
+}
@@ -0,0 +1,387 @@
+//! Server configuration.
+//!
+//! Values come from Rocket's figment: `Rocket.toml` and `ROCKET_*` variables
+//! as usual, overridden by the unprefixed variables in [`ENV_KEYS`], which is
+//! how `.env` and Heroku provide them. Keys in `Rocket.toml` are the same
+//! names in lowercase, e.g. `gh_client_id`.
+//!
+//! `SECRET_KEY` is Rocket's own `secret_key`, which encrypts the state cookie
+//! of the OAuth logins; release builds don't start without it.
+//!
+//! Everything is checked at ignition and each problem is reported, so a
+//! misconfigured instance refuses to start instead of failing on the first
+//! request that needs the missing value.
+use std::collections::HashMap;
+use std::path::PathBuf;
+use rocket::figment::Figment;
+use rocket::figment::providers::Env;
+use rocket::serde::Deserialize;
+use crate::rate_limit::{self, Limit, RouteClass};
+
+pub const ENV_KEYS: &[&str] = &[
+    "DATABASE_URL",
+    "REDIS_URL",
+    "STORAGE",
+    "STORAGE_PATH",
+    "GH_CLIENT_ID",
+    "GH_CLIENT_SECRET",
+    "GH_REDIRECT_URL",
+    "GH_URL",
+    "GH_API_URL",
+    "GITLAB_CLIENT_ID",
+    "GITLAB_CLIENT_SECRET",
+    "GITLAB_REDIRECT_URL",
+    "GITLAB_URL",
+    "GITEA_CLIENT_ID",
+    "GITEA_CLIENT_SECRET",
+    "GITEA_REDIRECT_URL",
+    "GITEA_URL",
+    "GITEA_NAME",
+    "LOG_SIGNING_KEY",
+    "ALLOWED_ORIGINS",
+    "RATE_LIMIT_LOGIN",
+    "RATE_LIMIT_PUBLISH",
+    "RATE_LIMIT_SEARCH",
+    "RATE_LIMIT_DOWNLOAD",
+    "CLIENT_IP_HEADER",
+    "METRICS_TOKEN",
+    "DEV_LOGIN",
+    "SECRET_KEY",
+];
+
+/// Rocket's own figment with our variables merged on top.
+pub fn figment() -> Figment {
+    rocket::Config::figment().merge(Env::raw().only(ENV_KEYS))
+}
+
+/// Where plugin archives are kept.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Storage {
+    Filesystem
+}
+
+/// OAuth app on an optional provider, which is enabled when its client id
+/// and secret are both set.
+#[derive(Debug)]
+pub struct OAuthApp {
+    pub client_id: String,
+    pub client_secret: String,
+    pub redirect_url: String,
+    /// Base URL of the instance, without a trailing slash.
+    pub url: String
+}
+
+#[derive(Debug)]
+pub struct Config {
+    pub database_url: String,
+    pub redis_url: String,
+    pub storage: Storage,
+    /// Root directory of the `filesystem` storage.
+    pub storage_path: PathBuf,
+    pub gh_client_id: String,
+    pub gh_client_secret: String,
+    pub gh_redirect_url: String,
+    /// Where users authorize the OAuth app and tokens are exchanged.
+    pub gh_url: String,
+    pub gh_api_url: String,
+    /// GitLab.com or a self-hosted GitLab.
+    pub gitlab: Option<OAuthApp>,
+    /// A Gitea or Forgejo instance, Codeberg by default.
+    pub gitea: Option<OAuthApp>,
+    /// What the Gitea instance is called on the login button.
+    pub gitea_name: String,
+    /// Raw ed25519 secret key signing transparency log tree heads.
+    pub log_signing_key: Option<Vec<u8>>,
+    /// Origins allowed to send requests with the session cookie besides our own.
+    pub allowed_origins: Vec<String>,
+    /// Overridden limits by route class name.
+    pub rate_limits: HashMap<String, Limit>,
+    /// Header the reverse proxy in front of the registry appends the client
+    /// address to, `X-Forwarded-For` on Heroku. Unset, rate limits count the
+    /// address of the connection.
+    pub client_ip_header: Option<String>,
+    /// Bearer token required to scrape `/metrics`, which is public without it.
+    pub metrics_token: Option<String>,
+    /// Log in without any provider at `/login/dev`. Only debug builds accept it.
+    pub dev_login: bool
+}
+
+/// Config as found in the figment, before validation.
+struct RawConfig {
+    database_url: Option<String>,
+    redis_url: Option<String>,
+    storage: Option<String>,
+    storage_path: Option<String>,
+    gh_client_id: Option<String>,
+    gh_client_secret: Option<String>,
+    gh_redirect_url: Option<String>,
+    gh_url: Option<String>,
+    gh_api_url: Option<String>,
+    gitlab_client_id: Option<String>,
+    gitlab_client_secret: Option<String>,
+    gitlab_redirect_url: Option<String>,
+    gitlab_url: Option<String>,
+    gitea_client_id: Option<String>,
+    gitea_client_secret: Option<String>,
+    gitea_redirect_url: Option<String>,
+    gitea_url: Option<String>,
+    gitea_name: Option<String>,
+    log_signing_key: Option<String>,
+    allowed_origins: Option<String>,
+    rate_limit_login: Option<String>,
+    rate_limit_publish: Option<String>,
+    rate_limit_search: Option<String>,
+    rate_limit_download: Option<String>,
+    client_ip_header: Option<String>,
+    metrics_token: Option<String>,
+    dev_login: bool
+}
+
+/// A value a provider gave as something else than a string: environment
+/// variables that look like numbers or booleans arrive as such.
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde", untagged)]
+enum Scalar {
+    Bool(bool),
+    Integer(i64),
+    Float(f64)
+}
+
+/// Reads the keys one by one as strings, so a value of the wrong type is
+/// reported with the other problems instead of stopping at the first.
+struct Reader<'a> {
+    figment: &'a Figment,
+    problems: Vec<String>
+}
+
+impl Reader<'_> {
+    fn string(&mut self, key: &str) -> Option<String> {
+        match self.figment.extract_inner::<String>(key) {
+            Ok(value) => Some(value),
+            Err(err) if err.missing() => None,
+            Err(_) => match self.figment.extract_inner::<Scalar>(key) {
+                Ok(Scalar::Bool(value)) => Some(value.to_string()),
+                Ok(Scalar::Integer(value)) => Some(value.to_string()),
+                Ok(Scalar::Float(value)) => Some(value.to_string()),
+                Err(_) => {
+                    self.problems.push(format!("{} must be a single value, not a list or table", key.to_uppercase()));
+                    None
+                }
+            }
+        }
+    }
+
+    fn flag(&mut self, key: &str) -> bool {
+        match self.string(key).as_deref().map(str::trim) {
+            None | Some("" | "false" | "0") => false,
+            Some("true" | "1") => true,
+            Some(other) => {
+                self.problems.push(format!("{} must be `true` or `false`, not `{other}`", key.to_uppercase()));
+                false
+            }
+        }
+    }
+
+    fn raw(&mut self) -> RawConfig {
+        RawConfig {
+            database_url: self.string("database_url"),
+            redis_url: self.string("redis_url"),
+            storage: self.string("storage"),
+            storage_path: self.string("storage_path"),
+            gh_client_id: self.string("gh_client_id"),
+            gh_client_secret: self.string("gh_client_secret"),
+            gh_redirect_url: self.string("gh_redirect_url"),
+            gh_url: self.string("gh_url"),
+            gh_api_url: self.string("gh_api_url"),
+            gitlab_client_id: self.string("gitlab_client_id"),
+            gitlab_client_secret: self.string("gitlab_client_secret"),
+            gitlab_redirect_url: self.string("gitlab_redirect_url"),
+            gitlab_url: self.string("gitlab_url"),
+            gitea_client_id: self.string("gitea_client_id"),
+            gitea_client_secret: self.string("gitea_client_secret"),
+            gitea_redirect_url: self.string("gitea_redirect_url"),
+            gitea_url: self.string("gitea_url"),
+            gitea_name: self.string("gitea_name"),
+            log_signing_key: self.string("log_signing_key"),
+            allowed_origins: self.string("allowed_origins"),
+            rate_limit_login: self.string("rate_limit_login"),
+            rate_limit_publish: self.string("rate_limit_publish"),
+            rate_limit_search: self.string("rate_limit_search"),
+            rate_limit_download: self.string("rate_limit_download"),
+            client_ip_header: self.string("client_ip_header"),
+            metrics_token: self.string("metrics_token"),
+            dev_login: self.flag("dev_login")
+        }
+    }
+}
+
+/// Every problem found in the configuration.
+#[derive(Debug)]
+pub struct ConfigError(pub Vec<String>);
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid configuration:")?;
+        for problem in &self.0 {
+            write!(f, "\n  - {problem}")?;
+        }
+        Ok(())
+    }
+}
+
+fn base_url(url: Option<String>, default: &str) -> String {
+    url.map(|url| url.trim_end_matches('/').to_string()).unwrap_or_else(|| default.into())
+}
+
+/// The app of an optional provider whose variables start with `prefix`.
+fn oauth_app(
+    prefix: &str,
+    client_id: Option<String>,
+    client_secret: Option<String>,
+    redirect_url: Option<String>,
+    url: Option<String>,
+    default_url: &str,
+    problems: &mut Vec<String>
+) -> Option<OAuthApp> {
+    let client_id = client_id.filter(|id| !id.trim().is_empty());
+    let client_secret = client_secret.filter(|secret| !secret.trim().is_empty());
+    match (client_id, client_secret) {
+        (Some(client_id), Some(client_secret)) => Some(OAuthApp {
+            client_id,
+            client_secret,
+            redirect_url: redirect_url.unwrap_or_else(|| format!("https://localhost:8000/auth/{}", prefix.to_lowercase())),
+            url: base_url(url, default_url)
+        }),
+        (None, None) => None,
+        _ => {
+            problems.push(format!("{prefix}_CLIENT_ID and {prefix}_CLIENT_SECRET must be set together"));
+            None
+        }
+    }
+}
+
+impl Config {
+    pub fn from_figment(figment: &Figment) -> Result<Self, ConfigError> {
+        let mut reader = Reader { figment, problems: vec![] };
+        let raw = reader.raw();
+        let mut problems = reader.problems;
+        let mut required = |key: &str, value: Option<String>| match value {
+            Some(value) if !value.trim().is_empty() => value,
+            _ => {
+                problems.push(format!("{key} is missing"));
+                String::new()
+            }
+        };
+        let database_url = required("DATABASE_URL", raw.database_url);
+        // GitHub can be left out when developing offline.
+        let dev_login = raw.dev_login;
+        let (gh_client_id, gh_client_secret) = if dev_login {
+            (raw.gh_client_id.unwrap_or_default(), raw.gh_client_secret.unwrap_or_default())
+        } else {
+            (required("GH_CLIENT_ID", raw.gh_client_id), required("GH_CLIENT_SECRET", raw.gh_client_secret))
+        };
+        if dev_login && !cfg!(debug_assertions) {
+            problems.push("DEV_LOGIN lets anyone log in as anyone, it's refused in release builds".into());
+        }
+
+        let redis_url = raw.redis_url.unwrap_or_else(|| "redis://localhost".into());
+        if let Err(err) = redis::Client::open(redis_url.as_str()) {
+            problems.push(format!("REDIS_URL isn't a Redis URL: {err}"));
+        }
+
+        let storage = match raw.storage.as_deref().unwrap_or("filesystem") {
+            "filesystem" => Storage::Filesystem,
+            other => {
+                problems.push(format!("STORAGE must be `filesystem`, not `{other}`"));
+                Storage::Filesystem
+            }
+        };
+
+        let log_signing_key = match raw.log_signing_key {
+            Some(key) => match base64::decode(key.trim()) {
+                Ok(key) if key.len() == ed25519_dalek::SECRET_KEY_LENGTH => Some(key),
+                _ => {
+                    problems.push("LOG_SIGNING_KEY must be a base64 encoded 32 byte ed25519 secret key, e.g. from `openssl rand -base64 32`".into());
+                    None
+                }
+            },
+            None => None
+        };
+
+        let allowed_origins: Vec<String> = raw.allowed_origins.unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|origin| !origin.is_empty())
+            .map(String::from)
+            .collect();
+        for origin in &allowed_origins {
+            if !origin.contains("://") {
+                problems.push(format!("ALLOWED_ORIGINS: `{origin}` isn't an origin like https://example.com"));
+            }
+        }
+
+        let gitlab = oauth_app(
+            "GITLAB",
+            raw.gitlab_client_id,
+            raw.gitlab_client_secret,
+            raw.gitlab_redirect_url,
+            raw.gitlab_url,
+            "https://gitlab.com",
+            &mut problems
+        );
+        let gitea = oauth_app(
+            "GITEA",
+            raw.gitea_client_id,
+            raw.gitea_client_secret,
+            raw.gitea_redirect_url,
+            raw.gitea_url,
+            "https://codeberg.org",
+            &mut problems
+        );
+
+        let mut rate_limits = HashMap::new();
+        let overrides = [
+            (rate_limit::Login::NAME, raw.rate_limit_login),
+            (rate_limit::Publish::NAME, raw.rate_limit_publish),
+            (rate_limit::Search::NAME, raw.rate_limit_search),
+            (rate_limit::Download::NAME, raw.rate_limit_download),
+        ];
+        for (name, value) in overrides {
+            if let Some(value) = value {
+                match Limit::parse(&value) {
+                    Some(limit) if limit.requests == 0 || limit.window.is_zero() => {
+                        problems.push(format!("RATE_LIMIT_{} can't have zero requests or seconds, `{value}` would refuse every request", name.to_uppercase()))
+                    }
+                    Some(limit) => {
+                        rate_limits.insert(name.to_string(), limit);
+                    }
+                    None => problems.push(format!("RATE_LIMIT_{} must look like `10/60` (requests/seconds), not `{value}`", name.to_uppercase()))
+                }
+            }
+        }
+
+        if !problems.is_empty() {
+            return Err(ConfigError(problems));
+        }
+        Ok(Self {
+            database_url,
+            redis_url,
+            storage,
+            storage_path: raw.storage_path.map(PathBuf::from).unwrap_or_else(|| "storage".into()),
+            gh_client_id,
+            gh_client_secret,
+            gh_redirect_url: raw.gh_redirect_url.unwrap_or_else(|| "https://localhost:8000/auth/github".into()),
+            gh_url: base_url(raw.gh_url, "https://github.com"),
+            gh_api_url: base_url(raw.gh_api_url, "https://api.github.com"),
+            gitlab,
+            gitea,
+            gitea_name: raw.gitea_name.unwrap_or_else(|| "Codeberg".into()),
+            log_signing_key,
+            allowed_origins,
+            rate_limits,
+            client_ip_header: raw.client_ip_header.map(|header| header.trim().to_string()).filter(|header| !header.is_empty()),
+            metrics_token: raw.metrics_token.filter(|token| !token.is_empty()),
+            dev_login
+        })
+    }
+}
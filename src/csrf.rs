@@ -0,0 +1,58 @@
+//! Cross-site request forgery protection.
+//!
+//! The session cookie is readable by scripts and sent on cross-site requests,
+//! so every state-changing request that carries it must come from one of our
+//! own pages. Browsers always send `Origin` (or at least `Referer`) on such
+//! requests; its host has to match the `Host` the request was sent to, or one
+//! of the extra origins listed in `ALLOWED_ORIGINS`.
+//!
+//! Requests without the session cookie can't be forged by another site and are
+//! left alone. An `Authorization` header doesn't exempt a request: no route
+//! that changes data authenticates with it, so the session cookie next to it
+//! is what the request acts on.
+use rocket::http::Method;
+use rocket::Request;
+use crate::config::Config;
+use crate::error::*;
+
+/// Whether `request` can be trusted to act on the session cookie `cookie`.
+pub fn is_trusted(request: &Request<'_>, cookie: &str) -> bool {
+    if matches!(request.method(), Method::Get | Method::Head | Method::Options) {
+        return true;
+    }
+    if request.cookies().get(cookie).is_none() {
+        return true;
+    }
+    let source = request.headers().get_one("Origin")
+        .or_else(|| request.headers().get_one("Referer"));
+    let source_host = match source.and_then(host_of) {
+        Some(host) => host,
+        None => return false
+    };
+    if request.headers().get_one("Host") == Some(source_host) {
+        return true;
+    }
+    request.rocket().state::<Config>()
+        .map(|config| config.allowed_origins.iter().filter_map(|origin| host_of(origin)).any(|host| host == source_host))
+        .unwrap_or(false)
+}
+
+/// `host[:port]` of an absolute URL.
+fn host_of(url: &str) -> Option<&str> {
+    let (_, rest) = url.split_once("://")?;
+    let host = rest.split(['/', '?', '#']).next()?;
+    if host.is_empty() { None } else { Some(host) }
+}
+
+pub fn cross_site_error() -> Error {
+    Error::new(
+        ErrorKind::Forbidden,
+        "Send requests that change data from the registry's own pages",
+        "Cross-site request refused"
+    )
+}
+
+#[catch(403)]
+pub fn forbidden(_: &Request<'_>) -> Error {
+    cross_site_error()
+}
@@ -1,22 +1,35 @@
+/// Generated from `prisma/schema.prisma` by `cargo prisma generate`. It isn't
+/// checked in.
 pub mod prisma;
-use dotenvy::dotenv;
 use prisma::PrismaClient;
 use crate::error::{Error, ErrorKind};
 
-pub async fn establish_connection() -> Result<PrismaClient, Error> {
-    dotenv().ok();
-    let res = prisma::new_client().await;
-    
-    match res {
-        Ok(con) => {
-            Ok(con)
-        }
-        Err(err) => {
-            Err(Error{
-                message: "Couldn't connect to the database".into(),
-                action: "Try again later, or if you're the admin, check the database".into(),
-                kind: ErrorKind::DatabaseError(err.to_string())
-            })
-        }
-    }
-}
\ No newline at end of file
+fn unreachable(err: impl ToString) -> Error {
+    Error::new(
+        ErrorKind::DatabaseError(err.to_string()),
+        "Check DATABASE_URL, and that the database is up and migrated",
+        "Couldn't connect to the database"
+    )
+}
+
+/// Connects to the database at `url` and checks that it answers. The server
+/// does this once at ignition and shares the client through managed state, so
+/// routes take a `&State<PrismaClient>` instead of connecting themselves.
+pub async fn establish_connection(url: &str) -> Result<PrismaClient, Error> {
+    let client = prisma::new_client_with_url(url).await.map_err(unreachable)?;
+    client.user().count(vec![]).exec().await.map_err(unreachable)?;
+    Ok(client)
+}
+
+/// Makes `next` the next id of `table`. Needed before rows are inserted with
+/// their own ids, as an import does, so later rows don't collide with them.
+pub async fn set_next_id(client: &PrismaClient, table: &str, next: i64) -> Result<(), Error> {
+    client._execute_raw(prisma_client_rust::raw!(
+        "SELECT setval(pg_get_serial_sequence({}, 'id'), {}, false)",
+        prisma_client_rust::PrismaValue::String(format!("\"{table}\"")),
+        prisma_client_rust::PrismaValue::BigInt(next)
+    ))
+        .exec().await
+        .map(|_| ())
+        .map_err(Error::database)
+}
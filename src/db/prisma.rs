@@ -1,1273 +0,0 @@
-// Code generated by Prisma Client Rust. DO NOT EDIT
-
-#![allow(warnings, unused)]
-static DATAMODEL_STR : & 'static str = "// This is your Prisma schema file,\n// learn more about it in the docs: https://pris.ly/d/prisma-schema\ngenerator client {\n    // Corresponds to the cargo alias created earlier\n    provider      = \"cargo prisma\"\n    // The location to generate the client. Is relative to the position of the schema\n    output        = \"../src/db/prisma.rs\"\n}\n\ndatasource db {\n  provider = \"postgresql\"\n  url      = env(\"DATABASE_URL\")\n}\nmodel User {\n\tid BigInt @id\n\tname String\n\tusername String\n\tavatar_url String\n}\n" ;
-static DATABASE_STR: &'static str = "postgresql";
-pub async fn new_client() -> Result<PrismaClient, ::prisma_client_rust::NewClientError> {
-    let config = ::prisma_client_rust::datamodel::parse_configuration(DATAMODEL_STR)?.subject;
-    let source = config
-        .datasources
-        .first()
-        .expect("Please supply a datasource in your schema.prisma file");
-    let url = if let Some(url) = source.load_shadow_database_url()? {
-        url
-    } else {
-        source.load_url(|key| std::env::var(key).ok())?
-    };
-    let url = if url.starts_with("file:") {
-        let path = url.split(":").nth(1).unwrap();
-        if std::path::Path::new("./schema.prisma").exists() {
-            url
-        } else if std::path::Path::new("./prisma/schema.prisma").exists() {
-            format!("file:./prisma/{}", path)
-        } else {
-            url
-        }
-    } else {
-        url
-    };
-    new_client_with_url(&url).await
-}
-pub async fn new_client_with_url(
-    url: &str,
-) -> Result<PrismaClient, ::prisma_client_rust::NewClientError> {
-    let config = ::prisma_client_rust::datamodel::parse_configuration(DATAMODEL_STR)?.subject;
-    let source = config
-        .datasources
-        .first()
-        .expect("Please supply a datasource in your schema.prisma file");
-    let (db_name, executor) =
-        ::prisma_client_rust::query_core::executor::load(&source, &[], &url).await?;
-    let internal_model =
-        ::prisma_client_rust::prisma_models::InternalDataModelBuilder::new(DATAMODEL_STR)
-            .build(db_name);
-    let query_schema = std::sync::Arc::new(prisma_client_rust::query_core::schema_builder::build(
-        internal_model,
-        true,
-        source.capabilities(),
-        vec![],
-        source.referential_integrity(),
-    ));
-    executor.primary_connector().get_connection().await?;
-    Ok(PrismaClient::_new(executor, query_schema))
-}
-pub mod user {
-    use super::_prisma::*;
-    use super::*;
-    pub mod id {
-        use super::super::*;
-        use super::_prisma::*;
-        use super::{OrderByParam, SetParam, UniqueWhereParam, WhereParam, WithParam};
-        pub fn set<T: From<Set>>(value: i64) -> T {
-            Set(value).into()
-        }
-        pub fn equals<T: From<UniqueWhereParam>>(value: i64) -> T {
-            UniqueWhereParam::IdEquals(value).into()
-        }
-        pub fn order(direction: ::prisma_client_rust::Direction) -> OrderByParam {
-            OrderByParam::Id(direction)
-        }
-        pub fn in_vec(value: Vec<i64>) -> WhereParam {
-            WhereParam::IdInVec(value)
-        }
-        pub fn not_in_vec(value: Vec<i64>) -> WhereParam {
-            WhereParam::IdNotInVec(value)
-        }
-        pub fn lt(value: i64) -> WhereParam {
-            WhereParam::IdLt(value)
-        }
-        pub fn lte(value: i64) -> WhereParam {
-            WhereParam::IdLte(value)
-        }
-        pub fn gt(value: i64) -> WhereParam {
-            WhereParam::IdGt(value)
-        }
-        pub fn gte(value: i64) -> WhereParam {
-            WhereParam::IdGte(value)
-        }
-        pub fn not(value: i64) -> WhereParam {
-            WhereParam::IdNot(value)
-        }
-        pub fn increment(value: i64) -> SetParam {
-            SetParam::IncrementId(value)
-        }
-        pub fn decrement(value: i64) -> SetParam {
-            SetParam::DecrementId(value)
-        }
-        pub fn multiply(value: i64) -> SetParam {
-            SetParam::MultiplyId(value)
-        }
-        pub fn divide(value: i64) -> SetParam {
-            SetParam::DivideId(value)
-        }
-        pub struct Set(pub i64);
-        impl From<Set> for SetParam {
-            fn from(value: Set) -> Self {
-                Self::SetId(value.0)
-            }
-        }
-        pub struct Include;
-        impl Into<super::IncludeParam> for Include {
-            fn into(self) -> super::IncludeParam {
-                super::IncludeParam::Id(self)
-            }
-        }
-        impl Include {
-            pub fn to_selection(self) -> ::prisma_client_rust::Selection {
-                ::prisma_client_rust::Selection::builder("id").build()
-            }
-        }
-        pub struct Select;
-        impl Into<super::SelectParam> for Select {
-            fn into(self) -> super::SelectParam {
-                super::SelectParam::Id(self)
-            }
-        }
-        impl Select {
-            pub fn to_selection(self) -> ::prisma_client_rust::Selection {
-                ::prisma_client_rust::Selection::builder("id").build()
-            }
-        }
-    }
-    pub mod name {
-        use super::super::*;
-        use super::_prisma::*;
-        use super::{OrderByParam, SetParam, UniqueWhereParam, WhereParam, WithParam};
-        pub fn set<T: From<Set>>(value: String) -> T {
-            Set(value).into()
-        }
-        pub fn equals(value: String) -> WhereParam {
-            WhereParam::NameEquals(value).into()
-        }
-        pub fn order(direction: ::prisma_client_rust::Direction) -> OrderByParam {
-            OrderByParam::Name(direction)
-        }
-        pub fn in_vec(value: Vec<String>) -> WhereParam {
-            WhereParam::NameInVec(value)
-        }
-        pub fn not_in_vec(value: Vec<String>) -> WhereParam {
-            WhereParam::NameNotInVec(value)
-        }
-        pub fn lt(value: String) -> WhereParam {
-            WhereParam::NameLt(value)
-        }
-        pub fn lte(value: String) -> WhereParam {
-            WhereParam::NameLte(value)
-        }
-        pub fn gt(value: String) -> WhereParam {
-            WhereParam::NameGt(value)
-        }
-        pub fn gte(value: String) -> WhereParam {
-            WhereParam::NameGte(value)
-        }
-        pub fn contains(value: String) -> WhereParam {
-            WhereParam::NameContains(value)
-        }
-        pub fn starts_with(value: String) -> WhereParam {
-            WhereParam::NameStartsWith(value)
-        }
-        pub fn ends_with(value: String) -> WhereParam {
-            WhereParam::NameEndsWith(value)
-        }
-        pub fn mode(value: QueryMode) -> WhereParam {
-            WhereParam::NameMode(value)
-        }
-        pub fn not(value: String) -> WhereParam {
-            WhereParam::NameNot(value)
-        }
-        pub struct Set(pub String);
-        impl From<Set> for SetParam {
-            fn from(value: Set) -> Self {
-                Self::SetName(value.0)
-            }
-        }
-        pub struct Include;
-        impl Into<super::IncludeParam> for Include {
-            fn into(self) -> super::IncludeParam {
-                super::IncludeParam::Name(self)
-            }
-        }
-        impl Include {
-            pub fn to_selection(self) -> ::prisma_client_rust::Selection {
-                ::prisma_client_rust::Selection::builder("name").build()
-            }
-        }
-        pub struct Select;
-        impl Into<super::SelectParam> for Select {
-            fn into(self) -> super::SelectParam {
-                super::SelectParam::Name(self)
-            }
-        }
-        impl Select {
-            pub fn to_selection(self) -> ::prisma_client_rust::Selection {
-                ::prisma_client_rust::Selection::builder("name").build()
-            }
-        }
-    }
-    pub mod username {
-        use super::super::*;
-        use super::_prisma::*;
-        use super::{OrderByParam, SetParam, UniqueWhereParam, WhereParam, WithParam};
-        pub fn set<T: From<Set>>(value: String) -> T {
-            Set(value).into()
-        }
-        pub fn equals(value: String) -> WhereParam {
-            WhereParam::UsernameEquals(value).into()
-        }
-        pub fn order(direction: ::prisma_client_rust::Direction) -> OrderByParam {
-            OrderByParam::Username(direction)
-        }
-        pub fn in_vec(value: Vec<String>) -> WhereParam {
-            WhereParam::UsernameInVec(value)
-        }
-        pub fn not_in_vec(value: Vec<String>) -> WhereParam {
-            WhereParam::UsernameNotInVec(value)
-        }
-        pub fn lt(value: String) -> WhereParam {
-            WhereParam::UsernameLt(value)
-        }
-        pub fn lte(value: String) -> WhereParam {
-            WhereParam::UsernameLte(value)
-        }
-        pub fn gt(value: String) -> WhereParam {
-            WhereParam::UsernameGt(value)
-        }
-        pub fn gte(value: String) -> WhereParam {
-            WhereParam::UsernameGte(value)
-        }
-        pub fn contains(value: String) -> WhereParam {
-            WhereParam::UsernameContains(value)
-        }
-        pub fn starts_with(value: String) -> WhereParam {
-            WhereParam::UsernameStartsWith(value)
-        }
-        pub fn ends_with(value: String) -> WhereParam {
-            WhereParam::UsernameEndsWith(value)
-        }
-        pub fn mode(value: QueryMode) -> WhereParam {
-            WhereParam::UsernameMode(value)
-        }
-        pub fn not(value: String) -> WhereParam {
-            WhereParam::UsernameNot(value)
-        }
-        pub struct Set(pub String);
-        impl From<Set> for SetParam {
-            fn from(value: Set) -> Self {
-                Self::SetUsername(value.0)
-            }
-        }
-        pub struct Include;
-        impl Into<super::IncludeParam> for Include {
-            fn into(self) -> super::IncludeParam {
-                super::IncludeParam::Username(self)
-            }
-        }
-        impl Include {
-            pub fn to_selection(self) -> ::prisma_client_rust::Selection {
-                ::prisma_client_rust::Selection::builder("username").build()
-            }
-        }
-        pub struct Select;
-        impl Into<super::SelectParam> for Select {
-            fn into(self) -> super::SelectParam {
-                super::SelectParam::Username(self)
-            }
-        }
-        impl Select {
-            pub fn to_selection(self) -> ::prisma_client_rust::Selection {
-                ::prisma_client_rust::Selection::builder("username").build()
-            }
-        }
-    }
-    pub mod avatar_url {
-        use super::super::*;
-        use super::_prisma::*;
-        use super::{OrderByParam, SetParam, UniqueWhereParam, WhereParam, WithParam};
-        pub fn set<T: From<Set>>(value: String) -> T {
-            Set(value).into()
-        }
-        pub fn equals(value: String) -> WhereParam {
-            WhereParam::AvatarUrlEquals(value).into()
-        }
-        pub fn order(direction: ::prisma_client_rust::Direction) -> OrderByParam {
-            OrderByParam::AvatarUrl(direction)
-        }
-        pub fn in_vec(value: Vec<String>) -> WhereParam {
-            WhereParam::AvatarUrlInVec(value)
-        }
-        pub fn not_in_vec(value: Vec<String>) -> WhereParam {
-            WhereParam::AvatarUrlNotInVec(value)
-        }
-        pub fn lt(value: String) -> WhereParam {
-            WhereParam::AvatarUrlLt(value)
-        }
-        pub fn lte(value: String) -> WhereParam {
-            WhereParam::AvatarUrlLte(value)
-        }
-        pub fn gt(value: String) -> WhereParam {
-            WhereParam::AvatarUrlGt(value)
-        }
-        pub fn gte(value: String) -> WhereParam {
-            WhereParam::AvatarUrlGte(value)
-        }
-        pub fn contains(value: String) -> WhereParam {
-            WhereParam::AvatarUrlContains(value)
-        }
-        pub fn starts_with(value: String) -> WhereParam {
-            WhereParam::AvatarUrlStartsWith(value)
-        }
-        pub fn ends_with(value: String) -> WhereParam {
-            WhereParam::AvatarUrlEndsWith(value)
-        }
-        pub fn mode(value: QueryMode) -> WhereParam {
-            WhereParam::AvatarUrlMode(value)
-        }
-        pub fn not(value: String) -> WhereParam {
-            WhereParam::AvatarUrlNot(value)
-        }
-        pub struct Set(pub String);
-        impl From<Set> for SetParam {
-            fn from(value: Set) -> Self {
-                Self::SetAvatarUrl(value.0)
-            }
-        }
-        pub struct Include;
-        impl Into<super::IncludeParam> for Include {
-            fn into(self) -> super::IncludeParam {
-                super::IncludeParam::AvatarUrl(self)
-            }
-        }
-        impl Include {
-            pub fn to_selection(self) -> ::prisma_client_rust::Selection {
-                ::prisma_client_rust::Selection::builder("avatar_url").build()
-            }
-        }
-        pub struct Select;
-        impl Into<super::SelectParam> for Select {
-            fn into(self) -> super::SelectParam {
-                super::SelectParam::AvatarUrl(self)
-            }
-        }
-        impl Select {
-            pub fn to_selection(self) -> ::prisma_client_rust::Selection {
-                ::prisma_client_rust::Selection::builder("avatar_url").build()
-            }
-        }
-    }
-    pub fn _outputs() -> Vec<::prisma_client_rust::Selection> {
-        ["id", "name", "username", "avatar_url"]
-            .into_iter()
-            .map(|o| {
-                let builder = ::prisma_client_rust::Selection::builder(o);
-                builder.build()
-            })
-            .collect()
-    }
-    pub fn create(
-        id: i64,
-        name: String,
-        username: String,
-        avatar_url: String,
-        _params: Vec<SetParam>,
-    ) -> (i64, String, String, String, Vec<SetParam>) {
-        (id, name, username, avatar_url, _params)
-    }
-    #[macro_export]
-    macro_rules ! _select_user { ($ (($ ($ func_arg : ident : $ func_arg_ty : ty) , +) =>) ? $ module_name : ident { $ ($ field : ident $ (($ ($ filters : tt) +) $ (. $ arg : ident ($ ($ arg_params : tt) *)) *) ? $ (: $ selection_mode : ident { $ ($ selections : tt) + }) ?) + }) => { # [allow (warnings)] pub mod $ module_name { $ crate :: prisma :: user :: select ! (@ definitions ; $ ($ field $ (($ ($ filters) +) $ (. $ arg ($ ($ arg_params) *)) *) ? $ (: $ selection_mode { $ ($ selections) + }) ?) +) ; pub struct Select (Vec < :: prisma_client_rust :: Selection >) ; impl :: prisma_client_rust :: select :: SelectType for Select { type Data = Data ; type ModelData = $ crate :: prisma :: user :: Data ; fn to_selections (self) -> Vec < :: prisma_client_rust :: Selection > { self . 0 } } use super :: * ; pub fn select ($ ($ ($ func_arg : $ func_arg_ty) , +) ?) -> Select { Select ($ crate :: prisma :: user :: select ! (@ selections_to_select_params ; : select { $ ($ field $ (($ ($ filters) +) $ (. $ arg ($ ($ arg_params) *)) *) ? $ (: $ selection_mode { $ ($ selections) + }) ?) + }) . into_iter () . map (| p | p . to_selection ()) . collect ()) } } } ; ({ $ ($ field : ident $ (($ ($ filters : tt) +) $ (. $ arg : ident ($ ($ arg_params : tt) *)) *) ? $ (: $ selection_mode : ident { $ ($ selections : tt) + }) ?) + }) => { { $ crate :: prisma :: user :: select ! (@ definitions ; $ ($ field $ (($ ($ filters) +) $ (. $ arg ($ ($ arg_params) *)) *) ? $ (: $ selection_mode { $ ($ selections) + }) ?) +) ; pub struct Select (Vec < :: prisma_client_rust :: Selection >) ; impl :: prisma_client_rust :: select :: SelectType for Select { type Data = Data ; type ModelData = $ crate :: prisma :: user :: Data ; fn to_selections (self) -> Vec < :: prisma_client_rust :: Selection > { self . 0 } } Select ($ crate :: prisma :: user :: select ! (@ selections_to_select_params ; : select { $ ($ field $ (($ ($ filters) +) $ (. $ arg ($ ($ arg_params) *)) *) ? $ (: $ selection_mode { $ ($ selections) + }) ?) + }) . into_iter () . map (| p | p . to_selection ()) . collect ()) } } ; (@ definitions ; $ ($ field : ident $ (($ ($ filters : tt) +) $ (. $ arg : ident ($ ($ arg_params : tt) *)) *) ? $ (: $ selection_mode : ident { $ ($ selections : tt) + }) ?) +) => { # [allow (warnings)] enum Fields { id , name , username , avatar_url } # [allow (warnings)] impl Fields { fn selections () { $ (let _ = Fields :: $ field ;) + } } # [allow (warnings)] # [derive (std :: fmt :: Debug)] pub struct Data { $ (pub $ field : $ crate :: prisma :: user :: select ! (@ field_type ; $ field $ (: $ selection_mode { $ ($ selections) + }) ?) ,) + } impl :: serde :: Serialize for Data { fn serialize < S > (& self , serializer : S) -> Result < S :: Ok , S :: Error > where S : :: serde :: Serializer , { use :: serde :: ser :: SerializeStruct ; let mut state = serializer . serialize_struct ("Data" , [$ (stringify ! ($ field) ,) + stringify ! (id) , stringify ! (name) , stringify ! (username) , stringify ! (avatar_url)] . len ()) ? ; $ (state . serialize_field ($ crate :: prisma :: user :: select ! (@ field_serde_name ; $ field) , & self . $ field) ? ;) * state . end () } } impl < 'de > :: serde :: Deserialize < 'de > for Data { fn deserialize < D > (deserializer : D) -> Result < Self , D :: Error > where D : :: serde :: Deserializer < 'de > , { # [allow (warnings)] enum Field { $ ($ field) , + , } impl < 'de > :: serde :: Deserialize < 'de > for Field { fn deserialize < D > (deserializer : D) -> Result < Field , D :: Error > where D : :: serde :: Deserializer < 'de > , { struct FieldVisitor ; impl < 'de > :: serde :: de :: Visitor < 'de > for FieldVisitor { type Value = Field ; fn expecting (& self , formatter : & mut :: std :: fmt :: Formatter) -> :: std :: fmt :: Result { formatter . write_str (concat ! ($ ($ crate :: prisma :: user :: select ! (@ field_serde_name ; $ field) , ", ") , +)) } fn visit_str < E > (self , value : & str) -> Result < Field , E > where E : :: serde :: de :: Error , { match value { $ ($ crate :: prisma :: user :: select ! (@ field_serde_name ; $ field) => Ok (Field :: $ field)) , * , _ => Err (:: serde :: de :: Error :: unknown_field (value , FIELDS)) , } } } deserializer . deserialize_identifier (FieldVisitor) } } struct DataVisitor ; impl < 'de > :: serde :: de :: Visitor < 'de > for DataVisitor { type Value = Data ; fn expecting (& self , formatter : & mut std :: fmt :: Formatter) -> std :: fmt :: Result { formatter . write_str ("struct Data") } fn visit_map < V > (self , mut map : V) -> Result < Data , V :: Error > where V : :: serde :: de :: MapAccess < 'de > , { $ (let mut $ field = None ;) * while let Some (key) = map . next_key () ? { match key { $ (Field :: $ field => { if $ field . is_some () { return Err (:: serde :: de :: Error :: duplicate_field ($ crate :: prisma :: user :: select ! (@ field_serde_name ; $ field))) ; } $ field = Some (map . next_value () ?) ; }) * } } $ (let $ field = $ field . ok_or_else (|| serde :: de :: Error :: missing_field ($ crate :: prisma :: user :: select ! (@ field_serde_name ; $ field))) ? ;) * Ok (Data { $ ($ field) , * }) } } const FIELDS : & 'static [& 'static str] = & ["id" , "name" , "username" , "avatar_url"] ; deserializer . deserialize_struct ("Data" , FIELDS , DataVisitor) } } $ ($ (pub mod $ field { $ crate :: prisma :: user :: select ! (@ field_module ; $ field : $ selection_mode { $ ($ selections) + }) ; }) ?) + } ; (@ field_type ; id) => { i64 } ; (@ field_type ; name) => { String } ; (@ field_type ; username) => { String } ; (@ field_type ; avatar_url) => { String } ; (@ field_type ; $ field : ident $ ($ tokens : tt) *) => { compile_error ! (stringify ! (Cannot select field nonexistent field $ field on model "User" , available fields are "id, name, username, avatar_url")) } ; (@ field_module ; $ ($ tokens : tt) *) => { } ; (@ selection_field_to_selection_param ; id) => { Into :: < $ crate :: prisma :: user :: SelectParam > :: into ($ crate :: prisma :: user :: id :: Select) } ; (@ selection_field_to_selection_param ; name) => { Into :: < $ crate :: prisma :: user :: SelectParam > :: into ($ crate :: prisma :: user :: name :: Select) } ; (@ selection_field_to_selection_param ; username) => { Into :: < $ crate :: prisma :: user :: SelectParam > :: into ($ crate :: prisma :: user :: username :: Select) } ; (@ selection_field_to_selection_param ; avatar_url) => { Into :: < $ crate :: prisma :: user :: SelectParam > :: into ($ crate :: prisma :: user :: avatar_url :: Select) } ; (@ selection_field_to_selection_param ; $ ($ tokens : tt) *) => { compile_error ! (stringify ! ($ ($ tokens) *)) } ; (@ selections_to_select_params ; : $ macro_name : ident { $ ($ field : ident $ (($ ($ filters : tt) +) $ (. $ arg : ident ($ ($ arg_params : tt) *)) *) ? $ (: $ selection_mode : ident { $ ($ selections : tt) + }) ?) + }) => { [$ ($ crate :: prisma :: user :: $ macro_name ! (@ selection_field_to_selection_param ; $ field $ (($ ($ filters) +) $ (. $ arg ($ ($ arg_params) *)) *) ? $ (: $ selection_mode { $ ($ selections) + }) ?) ,) +] } ; (@ filters_to_args ;) => { vec ! [] } ; (@ filters_to_args ; $ ($ t : tt) *) => { $ ($ t) * } ; (@ field_serde_name ; id) => { "id" } ; (@ field_serde_name ; name) => { "name" } ; (@ field_serde_name ; username) => { "username" } ; (@ field_serde_name ; avatar_url) => { "avatar_url" } ; }
-    pub use _select_user as select;
-    pub enum SelectParam {
-        Id(id::Select),
-        Name(name::Select),
-        Username(username::Select),
-        AvatarUrl(avatar_url::Select),
-    }
-    impl SelectParam {
-        pub fn to_selection(self) -> ::prisma_client_rust::Selection {
-            match self {
-                Self::Id(data) => data.to_selection(),
-                Self::Name(data) => data.to_selection(),
-                Self::Username(data) => data.to_selection(),
-                Self::AvatarUrl(data) => data.to_selection(),
-            }
-        }
-    }
-    #[macro_export]
-    macro_rules ! _include_user { ($ (($ ($ func_arg : ident : $ func_arg_ty : ty) , +) =>) ? $ module_name : ident { $ ($ field : ident $ (($ ($ filters : tt) +) $ (. $ arg : ident ($ ($ arg_params : tt) *)) *) ? $ (: $ selection_mode : ident { $ ($ selections : tt) + }) ?) + }) => { # [allow (warnings)] pub mod $ module_name { $ crate :: prisma :: user :: include ! (@ definitions ; $ ($ field $ (($ ($ filters) +) $ (. $ arg ($ ($ arg_params) *)) *) ? $ (: $ selection_mode { $ ($ selections) + }) ?) +) ; pub struct Include (Vec < :: prisma_client_rust :: Selection >) ; impl :: prisma_client_rust :: include :: IncludeType for Include { type Data = Data ; type ModelData = $ crate :: prisma :: user :: Data ; fn to_selections (self) -> Vec < :: prisma_client_rust :: Selection > { self . 0 } } use super :: * ; pub fn include ($ ($ ($ func_arg : $ func_arg_ty) , +) ?) -> Include { let mut selections = $ crate :: prisma :: user :: _outputs () ; selections . extend ($ crate :: prisma :: user :: include ! (@ selections_to_include_params ; : include { $ ($ field $ (($ ($ filters) +) $ (. $ arg ($ ($ arg_params) *)) *) ? $ (: $ selection_mode { $ ($ selections) + }) ?) + }) . into_iter () . map (| p | p . to_selection ())) ; Include (selections) } } } ; ({ $ ($ field : ident $ (($ ($ filters : tt) +) $ (. $ arg : ident ($ ($ arg_params : tt) *)) *) ? $ (: $ selection_mode : ident { $ ($ selections : tt) + }) ?) + }) => { { $ crate :: prisma :: user :: include ! (@ definitions ; $ ($ field $ (($ ($ filters) +) $ (. $ arg ($ ($ arg_params) *)) *) ? $ (: $ selection_mode { $ ($ selections) + }) ?) +) ; pub struct Include (Vec < :: prisma_client_rust :: Selection >) ; impl :: prisma_client_rust :: include :: IncludeType for Include { type Data = Data ; type ModelData = $ crate :: prisma :: user :: Data ; fn to_selections (self) -> Vec < :: prisma_client_rust :: Selection > { self . 0 } } Include ({ let mut selections = $ crate :: prisma :: user :: _outputs () ; selections . extend ($ crate :: prisma :: user :: include ! (@ selections_to_include_params ; : include { $ ($ field $ (($ ($ filters) +) $ (. $ arg ($ ($ arg_params) *)) *) ? $ (: $ selection_mode { $ ($ selections) + }) ?) + }) . into_iter () . map (| p | p . to_selection ())) ; selections }) } } ; (@ definitions ; $ ($ field : ident $ (($ ($ filters : tt) +) $ (. $ arg : ident ($ ($ arg_params : tt) *)) *) ? $ (: $ selection_mode : ident { $ ($ selections : tt) + }) ?) +) => { # [allow (warnings)] enum Fields { } # [allow (warnings)] impl Fields { fn selections () { $ (let _ = Fields :: $ field ;) + } } # [allow (warnings)] # [derive (std :: fmt :: Debug)] pub struct Data { pub id : i64 , pub name : String , pub username : String , pub avatar_url : String , $ (pub $ field : $ crate :: prisma :: user :: include ! (@ field_type ; $ field $ (: $ selection_mode { $ ($ selections) + }) ?) ,) + } impl :: serde :: Serialize for Data { fn serialize < S > (& self , serializer : S) -> Result < S :: Ok , S :: Error > where S : :: serde :: Serializer , { use :: serde :: ser :: SerializeStruct ; let mut state = serializer . serialize_struct ("Data" , [$ (stringify ! ($ field) ,) + stringify ! (id) , stringify ! (name) , stringify ! (username) , stringify ! (avatar_url)] . len ()) ? ; $ (state . serialize_field ($ crate :: prisma :: user :: include ! (@ field_serde_name ; $ field) , & self . $ field) ? ;) * state . serialize_field ($ crate :: prisma :: user :: include ! (@ field_serde_name ; id) , & self . id) ? ; state . serialize_field ($ crate :: prisma :: user :: include ! (@ field_serde_name ; name) , & self . name) ? ; state . serialize_field ($ crate :: prisma :: user :: include ! (@ field_serde_name ; username) , & self . username) ? ; state . serialize_field ($ crate :: prisma :: user :: include ! (@ field_serde_name ; avatar_url) , & self . avatar_url) ? ; state . end () } } impl < 'de > :: serde :: Deserialize < 'de > for Data { fn deserialize < D > (deserializer : D) -> Result < Self , D :: Error > where D : :: serde :: Deserializer < 'de > , { # [allow (warnings)] enum Field { $ ($ field) , + , id , name , username , avatar_url } impl < 'de > :: serde :: Deserialize < 'de > for Field { fn deserialize < D > (deserializer : D) -> Result < Field , D :: Error > where D : :: serde :: Deserializer < 'de > , { struct FieldVisitor ; impl < 'de > :: serde :: de :: Visitor < 'de > for FieldVisitor { type Value = Field ; fn expecting (& self , formatter : & mut :: std :: fmt :: Formatter) -> :: std :: fmt :: Result { formatter . write_str (concat ! ($ ($ crate :: prisma :: user :: include ! (@ field_serde_name ; $ field) , ", ") , + , $ crate :: prisma :: user :: include ! (@ field_serde_name ; id) , ", " , $ crate :: prisma :: user :: include ! (@ field_serde_name ; name) , ", " , $ crate :: prisma :: user :: include ! (@ field_serde_name ; username) , ", " , $ crate :: prisma :: user :: include ! (@ field_serde_name ; avatar_url) , ", ")) } fn visit_str < E > (self , value : & str) -> Result < Field , E > where E : :: serde :: de :: Error , { match value { $ ($ crate :: prisma :: user :: include ! (@ field_serde_name ; $ field) => Ok (Field :: $ field)) , * , $ crate :: prisma :: user :: include ! (@ field_serde_name ; id) => Ok (Field :: id) , $ crate :: prisma :: user :: include ! (@ field_serde_name ; name) => Ok (Field :: name) , $ crate :: prisma :: user :: include ! (@ field_serde_name ; username) => Ok (Field :: username) , $ crate :: prisma :: user :: include ! (@ field_serde_name ; avatar_url) => Ok (Field :: avatar_url) , _ => Err (:: serde :: de :: Error :: unknown_field (value , FIELDS)) , } } } deserializer . deserialize_identifier (FieldVisitor) } } struct DataVisitor ; impl < 'de > :: serde :: de :: Visitor < 'de > for DataVisitor { type Value = Data ; fn expecting (& self , formatter : & mut std :: fmt :: Formatter) -> std :: fmt :: Result { formatter . write_str ("struct Data") } fn visit_map < V > (self , mut map : V) -> Result < Data , V :: Error > where V : :: serde :: de :: MapAccess < 'de > , { $ (let mut $ field = None ;) * let mut id = None ; let mut name = None ; let mut username = None ; let mut avatar_url = None ; while let Some (key) = map . next_key () ? { match key { Field :: id => { if id . is_some () { return Err (:: serde :: de :: Error :: duplicate_field ($ crate :: prisma :: user :: include ! (@ field_serde_name ; id))) ; } id = Some (map . next_value () ?) ; } Field :: name => { if name . is_some () { return Err (:: serde :: de :: Error :: duplicate_field ($ crate :: prisma :: user :: include ! (@ field_serde_name ; name))) ; } name = Some (map . next_value () ?) ; } Field :: username => { if username . is_some () { return Err (:: serde :: de :: Error :: duplicate_field ($ crate :: prisma :: user :: include ! (@ field_serde_name ; username))) ; } username = Some (map . next_value () ?) ; } Field :: avatar_url => { if avatar_url . is_some () { return Err (:: serde :: de :: Error :: duplicate_field ($ crate :: prisma :: user :: include ! (@ field_serde_name ; avatar_url))) ; } avatar_url = Some (map . next_value () ?) ; } $ (Field :: $ field => { if $ field . is_some () { return Err (:: serde :: de :: Error :: duplicate_field ($ crate :: prisma :: user :: include ! (@ field_serde_name ; $ field))) ; } $ field = Some (map . next_value () ?) ; }) * } } $ (let $ field = $ field . ok_or_else (|| serde :: de :: Error :: missing_field ($ crate :: prisma :: user :: include ! (@ field_serde_name ; $ field))) ? ;) * let id = id . ok_or_else (|| serde :: de :: Error :: missing_field ($ crate :: prisma :: user :: include ! (@ field_serde_name ; id))) ? ; let name = name . ok_or_else (|| serde :: de :: Error :: missing_field ($ crate :: prisma :: user :: include ! (@ field_serde_name ; name))) ? ; let username = username . ok_or_else (|| serde :: de :: Error :: missing_field ($ crate :: prisma :: user :: include ! (@ field_serde_name ; username))) ? ; let avatar_url = avatar_url . ok_or_else (|| serde :: de :: Error :: missing_field ($ crate :: prisma :: user :: include ! (@ field_serde_name ; avatar_url))) ? ; Ok (Data { id , name , username , avatar_url , $ ($ field) , * }) } } const FIELDS : & 'static [& 'static str] = & ["id" , "name" , "username" , "avatar_url"] ; deserializer . deserialize_struct ("Data" , FIELDS , DataVisitor) } } $ ($ (pub mod $ field { $ crate :: prisma :: user :: $ selection_mode ! (@ field_module ; $ field : $ selection_mode { $ ($ selections) + }) ; }) ?) + } ; (@ field_type ; $ field : ident $ ($ tokens : tt) *) => { compile_error ! (stringify ! (Cannot include nonexistent relation $ field on model "User" , available relations are "")) } ; (@ field_module ; $ ($ tokens : tt) *) => { } ; (@ selection_field_to_selection_param ; $ ($ tokens : tt) *) => { compile_error ! (stringify ! ($ ($ tokens) *)) } ; (@ selections_to_include_params ; : $ macro_name : ident { $ ($ field : ident $ (($ ($ filters : tt) +) $ (. $ arg : ident ($ ($ arg_params : tt) *)) *) ? $ (: $ selection_mode : ident { $ ($ selections : tt) + }) ?) + }) => { [$ ($ crate :: prisma :: user :: $ macro_name ! (@ selection_field_to_selection_param ; $ field $ (($ ($ filters) +) $ (. $ arg ($ ($ arg_params) *)) *) ? $ (: $ selection_mode { $ ($ selections) + }) ?) ,) +] } ; (@ filters_to_args ;) => { vec ! [] } ; (@ filters_to_args ; $ ($ t : tt) *) => { $ ($ t) * } ; (@ field_serde_name ; id) => { "id" } ; (@ field_serde_name ; name) => { "name" } ; (@ field_serde_name ; username) => { "username" } ; (@ field_serde_name ; avatar_url) => { "avatar_url" } ; }
-    pub use _include_user as include;
-    pub enum IncludeParam {
-        Id(id::Include),
-        Name(name::Include),
-        Username(username::Include),
-        AvatarUrl(avatar_url::Include),
-    }
-    impl IncludeParam {
-        pub fn to_selection(self) -> ::prisma_client_rust::Selection {
-            match self {
-                Self::Id(data) => data.to_selection(),
-                Self::Name(data) => data.to_selection(),
-                Self::Username(data) => data.to_selection(),
-                Self::AvatarUrl(data) => data.to_selection(),
-            }
-        }
-    }
-    #[derive(Debug, Clone, :: serde :: Serialize, :: serde :: Deserialize)]
-    pub struct Data {
-        #[serde(rename = "id")]
-        pub id: i64,
-        #[serde(rename = "name")]
-        pub name: String,
-        #[serde(rename = "username")]
-        pub username: String,
-        #[serde(rename = "avatar_url")]
-        pub avatar_url: String,
-    }
-    impl Data {}
-    #[derive(Clone)]
-    pub enum WithParam {}
-    impl Into<::prisma_client_rust::Selection> for WithParam {
-        fn into(self) -> ::prisma_client_rust::Selection {
-            match self {}
-        }
-    }
-    #[derive(Clone)]
-    pub enum SetParam {
-        SetId(i64),
-        IncrementId(i64),
-        DecrementId(i64),
-        MultiplyId(i64),
-        DivideId(i64),
-        SetName(String),
-        SetUsername(String),
-        SetAvatarUrl(String),
-    }
-    impl Into<(String, ::prisma_client_rust::PrismaValue)> for SetParam {
-        fn into(self) -> (String, ::prisma_client_rust::PrismaValue) {
-            match self {
-                SetParam::SetId(value) => (
-                    "id".to_string(),
-                    ::prisma_client_rust::PrismaValue::BigInt(value),
-                ),
-                SetParam::IncrementId(value) => (
-                    "id".to_string(),
-                    ::prisma_client_rust::PrismaValue::Object(vec![(
-                        "increment".to_string(),
-                        ::prisma_client_rust::PrismaValue::BigInt(value),
-                    )]),
-                ),
-                SetParam::DecrementId(value) => (
-                    "id".to_string(),
-                    ::prisma_client_rust::PrismaValue::Object(vec![(
-                        "decrement".to_string(),
-                        ::prisma_client_rust::PrismaValue::BigInt(value),
-                    )]),
-                ),
-                SetParam::MultiplyId(value) => (
-                    "id".to_string(),
-                    ::prisma_client_rust::PrismaValue::Object(vec![(
-                        "multiply".to_string(),
-                        ::prisma_client_rust::PrismaValue::BigInt(value),
-                    )]),
-                ),
-                SetParam::DivideId(value) => (
-                    "id".to_string(),
-                    ::prisma_client_rust::PrismaValue::Object(vec![(
-                        "divide".to_string(),
-                        ::prisma_client_rust::PrismaValue::BigInt(value),
-                    )]),
-                ),
-                SetParam::SetName(value) => (
-                    "name".to_string(),
-                    ::prisma_client_rust::PrismaValue::String(value),
-                ),
-                SetParam::SetUsername(value) => (
-                    "username".to_string(),
-                    ::prisma_client_rust::PrismaValue::String(value),
-                ),
-                SetParam::SetAvatarUrl(value) => (
-                    "avatar_url".to_string(),
-                    ::prisma_client_rust::PrismaValue::String(value),
-                ),
-            }
-        }
-    }
-    #[derive(Clone)]
-    pub enum OrderByParam {
-        Id(::prisma_client_rust::Direction),
-        Name(::prisma_client_rust::Direction),
-        Username(::prisma_client_rust::Direction),
-        AvatarUrl(::prisma_client_rust::Direction),
-    }
-    impl Into<(String, ::prisma_client_rust::PrismaValue)> for OrderByParam {
-        fn into(self) -> (String, ::prisma_client_rust::PrismaValue) {
-            match self {
-                Self::Id(direction) => (
-                    "id".to_string(),
-                    ::prisma_client_rust::PrismaValue::String(direction.to_string()),
-                ),
-                Self::Name(direction) => (
-                    "name".to_string(),
-                    ::prisma_client_rust::PrismaValue::String(direction.to_string()),
-                ),
-                Self::Username(direction) => (
-                    "username".to_string(),
-                    ::prisma_client_rust::PrismaValue::String(direction.to_string()),
-                ),
-                Self::AvatarUrl(direction) => (
-                    "avatar_url".to_string(),
-                    ::prisma_client_rust::PrismaValue::String(direction.to_string()),
-                ),
-            }
-        }
-    }
-    #[derive(Clone)]
-    pub enum WhereParam {
-        Not(Vec<WhereParam>),
-        Or(Vec<WhereParam>),
-        And(Vec<WhereParam>),
-        IdEquals(i64),
-        IdInVec(Vec<i64>),
-        IdNotInVec(Vec<i64>),
-        IdLt(i64),
-        IdLte(i64),
-        IdGt(i64),
-        IdGte(i64),
-        IdNot(i64),
-        NameEquals(String),
-        NameInVec(Vec<String>),
-        NameNotInVec(Vec<String>),
-        NameLt(String),
-        NameLte(String),
-        NameGt(String),
-        NameGte(String),
-        NameContains(String),
-        NameStartsWith(String),
-        NameEndsWith(String),
-        NameMode(QueryMode),
-        NameNot(String),
-        UsernameEquals(String),
-        UsernameInVec(Vec<String>),
-        UsernameNotInVec(Vec<String>),
-        UsernameLt(String),
-        UsernameLte(String),
-        UsernameGt(String),
-        UsernameGte(String),
-        UsernameContains(String),
-        UsernameStartsWith(String),
-        UsernameEndsWith(String),
-        UsernameMode(QueryMode),
-        UsernameNot(String),
-        AvatarUrlEquals(String),
-        AvatarUrlInVec(Vec<String>),
-        AvatarUrlNotInVec(Vec<String>),
-        AvatarUrlLt(String),
-        AvatarUrlLte(String),
-        AvatarUrlGt(String),
-        AvatarUrlGte(String),
-        AvatarUrlContains(String),
-        AvatarUrlStartsWith(String),
-        AvatarUrlEndsWith(String),
-        AvatarUrlMode(QueryMode),
-        AvatarUrlNot(String),
-    }
-    impl Into<::prisma_client_rust::SerializedWhere> for WhereParam {
-        fn into(self) -> ::prisma_client_rust::SerializedWhere {
-            match self {
-                Self::Not(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "NOT",
-                    ::prisma_client_rust::SerializedWhereValue::Object(
-                        value
-                            .into_iter()
-                            .map(Into::<::prisma_client_rust::SerializedWhere>::into)
-                            .map(Into::into)
-                            .collect(),
-                    ),
-                ),
-                Self::Or(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "OR",
-                    ::prisma_client_rust::SerializedWhereValue::List(
-                        value
-                            .into_iter()
-                            .map(Into::<::prisma_client_rust::SerializedWhere>::into)
-                            .map(Into::into)
-                            .map(|v| vec![v])
-                            .map(::prisma_client_rust::PrismaValue::Object)
-                            .collect(),
-                    ),
-                ),
-                Self::And(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "AND",
-                    ::prisma_client_rust::SerializedWhereValue::Object(
-                        value
-                            .into_iter()
-                            .map(Into::<::prisma_client_rust::SerializedWhere>::into)
-                            .map(Into::into)
-                            .collect(),
-                    ),
-                ),
-                Self::IdEquals(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "id",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "equals".to_string(),
-                        ::prisma_client_rust::PrismaValue::BigInt(value),
-                    )]),
-                ),
-                Self::IdInVec(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "id",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "in".to_string(),
-                        ::prisma_client_rust::PrismaValue::List(
-                            value
-                                .into_iter()
-                                .map(|v| ::prisma_client_rust::PrismaValue::BigInt(v))
-                                .collect(),
-                        ),
-                    )]),
-                ),
-                Self::IdNotInVec(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "id",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "notIn".to_string(),
-                        ::prisma_client_rust::PrismaValue::List(
-                            value
-                                .into_iter()
-                                .map(|v| ::prisma_client_rust::PrismaValue::BigInt(v))
-                                .collect(),
-                        ),
-                    )]),
-                ),
-                Self::IdLt(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "id",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "lt".to_string(),
-                        ::prisma_client_rust::PrismaValue::BigInt(value),
-                    )]),
-                ),
-                Self::IdLte(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "id",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "lte".to_string(),
-                        ::prisma_client_rust::PrismaValue::BigInt(value),
-                    )]),
-                ),
-                Self::IdGt(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "id",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "gt".to_string(),
-                        ::prisma_client_rust::PrismaValue::BigInt(value),
-                    )]),
-                ),
-                Self::IdGte(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "id",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "gte".to_string(),
-                        ::prisma_client_rust::PrismaValue::BigInt(value),
-                    )]),
-                ),
-                Self::IdNot(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "id",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "not".to_string(),
-                        ::prisma_client_rust::PrismaValue::BigInt(value),
-                    )]),
-                ),
-                Self::NameEquals(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "name",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "equals".to_string(),
-                        ::prisma_client_rust::PrismaValue::String(value),
-                    )]),
-                ),
-                Self::NameInVec(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "name",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "in".to_string(),
-                        ::prisma_client_rust::PrismaValue::List(
-                            value
-                                .into_iter()
-                                .map(|v| ::prisma_client_rust::PrismaValue::String(v))
-                                .collect(),
-                        ),
-                    )]),
-                ),
-                Self::NameNotInVec(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "name",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "notIn".to_string(),
-                        ::prisma_client_rust::PrismaValue::List(
-                            value
-                                .into_iter()
-                                .map(|v| ::prisma_client_rust::PrismaValue::String(v))
-                                .collect(),
-                        ),
-                    )]),
-                ),
-                Self::NameLt(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "name",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "lt".to_string(),
-                        ::prisma_client_rust::PrismaValue::String(value),
-                    )]),
-                ),
-                Self::NameLte(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "name",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "lte".to_string(),
-                        ::prisma_client_rust::PrismaValue::String(value),
-                    )]),
-                ),
-                Self::NameGt(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "name",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "gt".to_string(),
-                        ::prisma_client_rust::PrismaValue::String(value),
-                    )]),
-                ),
-                Self::NameGte(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "name",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "gte".to_string(),
-                        ::prisma_client_rust::PrismaValue::String(value),
-                    )]),
-                ),
-                Self::NameContains(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "name",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "contains".to_string(),
-                        ::prisma_client_rust::PrismaValue::String(value),
-                    )]),
-                ),
-                Self::NameStartsWith(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "name",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "startsWith".to_string(),
-                        ::prisma_client_rust::PrismaValue::String(value),
-                    )]),
-                ),
-                Self::NameEndsWith(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "name",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "endsWith".to_string(),
-                        ::prisma_client_rust::PrismaValue::String(value),
-                    )]),
-                ),
-                Self::NameMode(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "name",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "mode".to_string(),
-                        ::prisma_client_rust::PrismaValue::Enum(value.to_string()),
-                    )]),
-                ),
-                Self::NameNot(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "name",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "not".to_string(),
-                        ::prisma_client_rust::PrismaValue::String(value),
-                    )]),
-                ),
-                Self::UsernameEquals(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "username",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "equals".to_string(),
-                        ::prisma_client_rust::PrismaValue::String(value),
-                    )]),
-                ),
-                Self::UsernameInVec(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "username",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "in".to_string(),
-                        ::prisma_client_rust::PrismaValue::List(
-                            value
-                                .into_iter()
-                                .map(|v| ::prisma_client_rust::PrismaValue::String(v))
-                                .collect(),
-                        ),
-                    )]),
-                ),
-                Self::UsernameNotInVec(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "username",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "notIn".to_string(),
-                        ::prisma_client_rust::PrismaValue::List(
-                            value
-                                .into_iter()
-                                .map(|v| ::prisma_client_rust::PrismaValue::String(v))
-                                .collect(),
-                        ),
-                    )]),
-                ),
-                Self::UsernameLt(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "username",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "lt".to_string(),
-                        ::prisma_client_rust::PrismaValue::String(value),
-                    )]),
-                ),
-                Self::UsernameLte(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "username",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "lte".to_string(),
-                        ::prisma_client_rust::PrismaValue::String(value),
-                    )]),
-                ),
-                Self::UsernameGt(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "username",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "gt".to_string(),
-                        ::prisma_client_rust::PrismaValue::String(value),
-                    )]),
-                ),
-                Self::UsernameGte(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "username",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "gte".to_string(),
-                        ::prisma_client_rust::PrismaValue::String(value),
-                    )]),
-                ),
-                Self::UsernameContains(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "username",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "contains".to_string(),
-                        ::prisma_client_rust::PrismaValue::String(value),
-                    )]),
-                ),
-                Self::UsernameStartsWith(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "username",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "startsWith".to_string(),
-                        ::prisma_client_rust::PrismaValue::String(value),
-                    )]),
-                ),
-                Self::UsernameEndsWith(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "username",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "endsWith".to_string(),
-                        ::prisma_client_rust::PrismaValue::String(value),
-                    )]),
-                ),
-                Self::UsernameMode(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "username",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "mode".to_string(),
-                        ::prisma_client_rust::PrismaValue::Enum(value.to_string()),
-                    )]),
-                ),
-                Self::UsernameNot(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "username",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "not".to_string(),
-                        ::prisma_client_rust::PrismaValue::String(value),
-                    )]),
-                ),
-                Self::AvatarUrlEquals(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "avatar_url",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "equals".to_string(),
-                        ::prisma_client_rust::PrismaValue::String(value),
-                    )]),
-                ),
-                Self::AvatarUrlInVec(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "avatar_url",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "in".to_string(),
-                        ::prisma_client_rust::PrismaValue::List(
-                            value
-                                .into_iter()
-                                .map(|v| ::prisma_client_rust::PrismaValue::String(v))
-                                .collect(),
-                        ),
-                    )]),
-                ),
-                Self::AvatarUrlNotInVec(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "avatar_url",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "notIn".to_string(),
-                        ::prisma_client_rust::PrismaValue::List(
-                            value
-                                .into_iter()
-                                .map(|v| ::prisma_client_rust::PrismaValue::String(v))
-                                .collect(),
-                        ),
-                    )]),
-                ),
-                Self::AvatarUrlLt(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "avatar_url",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "lt".to_string(),
-                        ::prisma_client_rust::PrismaValue::String(value),
-                    )]),
-                ),
-                Self::AvatarUrlLte(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "avatar_url",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "lte".to_string(),
-                        ::prisma_client_rust::PrismaValue::String(value),
-                    )]),
-                ),
-                Self::AvatarUrlGt(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "avatar_url",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "gt".to_string(),
-                        ::prisma_client_rust::PrismaValue::String(value),
-                    )]),
-                ),
-                Self::AvatarUrlGte(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "avatar_url",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "gte".to_string(),
-                        ::prisma_client_rust::PrismaValue::String(value),
-                    )]),
-                ),
-                Self::AvatarUrlContains(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "avatar_url",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "contains".to_string(),
-                        ::prisma_client_rust::PrismaValue::String(value),
-                    )]),
-                ),
-                Self::AvatarUrlStartsWith(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "avatar_url",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "startsWith".to_string(),
-                        ::prisma_client_rust::PrismaValue::String(value),
-                    )]),
-                ),
-                Self::AvatarUrlEndsWith(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "avatar_url",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "endsWith".to_string(),
-                        ::prisma_client_rust::PrismaValue::String(value),
-                    )]),
-                ),
-                Self::AvatarUrlMode(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "avatar_url",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "mode".to_string(),
-                        ::prisma_client_rust::PrismaValue::Enum(value.to_string()),
-                    )]),
-                ),
-                Self::AvatarUrlNot(value) => ::prisma_client_rust::SerializedWhere::new(
-                    "avatar_url",
-                    ::prisma_client_rust::SerializedWhereValue::Object(vec![(
-                        "not".to_string(),
-                        ::prisma_client_rust::PrismaValue::String(value),
-                    )]),
-                ),
-            }
-        }
-    }
-    #[derive(Clone)]
-    pub enum UniqueWhereParam {
-        IdEquals(i64),
-    }
-    impl From<UniqueWhereParam> for WhereParam {
-        fn from(value: UniqueWhereParam) -> Self {
-            match value {
-                UniqueWhereParam::IdEquals(value) => Self::IdEquals(value),
-            }
-        }
-    }
-    impl From<::prisma_client_rust::Operator<Self>> for WhereParam {
-        fn from(op: ::prisma_client_rust::Operator<Self>) -> Self {
-            match op {
-                ::prisma_client_rust::Operator::Not(value) => Self::Not(value),
-                ::prisma_client_rust::Operator::And(value) => Self::And(value),
-                ::prisma_client_rust::Operator::Or(value) => Self::Or(value),
-            }
-        }
-    }
-    pub type UniqueArgs = ::prisma_client_rust::UniqueArgs<WithParam>;
-    pub type ManyArgs =
-        ::prisma_client_rust::ManyArgs<WhereParam, WithParam, OrderByParam, UniqueWhereParam>;
-    pub type Count<'a> =
-        ::prisma_client_rust::Count<'a, WhereParam, OrderByParam, UniqueWhereParam>;
-    pub type Create<'a> = ::prisma_client_rust::Create<'a, SetParam, WithParam, Data>;
-    pub type CreateMany<'a> = ::prisma_client_rust::CreateMany<'a, SetParam>;
-    pub type FindUnique<'a> =
-        ::prisma_client_rust::FindUnique<'a, WhereParam, WithParam, SetParam, Data>;
-    pub type FindMany<'a> = ::prisma_client_rust::FindMany<
-        'a,
-        WhereParam,
-        WithParam,
-        OrderByParam,
-        UniqueWhereParam,
-        SetParam,
-        Data,
-    >;
-    pub type FindFirst<'a> = ::prisma_client_rust::FindFirst<
-        'a,
-        WhereParam,
-        WithParam,
-        OrderByParam,
-        UniqueWhereParam,
-        Data,
-    >;
-    pub type Update<'a> = ::prisma_client_rust::Update<'a, WhereParam, WithParam, SetParam, Data>;
-    pub type UpdateMany<'a> = ::prisma_client_rust::UpdateMany<'a, WhereParam, SetParam>;
-    pub type Upsert<'a> = ::prisma_client_rust::Upsert<'a, WhereParam, SetParam, WithParam, Data>;
-    pub type Delete<'a> = ::prisma_client_rust::Delete<'a, WhereParam, WithParam, Data>;
-    pub type DeleteMany<'a> = ::prisma_client_rust::DeleteMany<'a, WhereParam>;
-    pub struct Actions<'a> {
-        pub client: &'a PrismaClient,
-    }
-    impl<'a> Actions<'a> {
-        pub fn find_unique(self, _where: UniqueWhereParam) -> FindUnique<'a> {
-            FindUnique::new(
-                self.client._new_query_context(),
-                ::prisma_client_rust::QueryInfo::new("User", _outputs()),
-                _where.into(),
-            )
-        }
-        pub fn find_first(self, _where: Vec<WhereParam>) -> FindFirst<'a> {
-            FindFirst::new(
-                self.client._new_query_context(),
-                ::prisma_client_rust::QueryInfo::new("User", _outputs()),
-                _where,
-            )
-        }
-        pub fn find_many(self, _where: Vec<WhereParam>) -> FindMany<'a> {
-            FindMany::new(
-                self.client._new_query_context(),
-                ::prisma_client_rust::QueryInfo::new("User", _outputs()),
-                _where,
-            )
-        }
-        pub fn create(
-            self,
-            id: i64,
-            name: String,
-            username: String,
-            avatar_url: String,
-            mut _params: Vec<SetParam>,
-        ) -> Create<'a> {
-            _params.push(id::set(id));
-            _params.push(name::set(name));
-            _params.push(username::set(username));
-            _params.push(avatar_url::set(avatar_url));
-            Create::new(
-                self.client._new_query_context(),
-                ::prisma_client_rust::QueryInfo::new("User", _outputs()),
-                _params,
-            )
-        }
-        pub fn create_many(
-            self,
-            data: Vec<(i64, String, String, String, Vec<SetParam>)>,
-        ) -> CreateMany<'a> {
-            let data = data
-                .into_iter()
-                .map(|(id, name, username, avatar_url, mut _params)| {
-                    _params.push(id::set(id));
-                    _params.push(name::set(name));
-                    _params.push(username::set(username));
-                    _params.push(avatar_url::set(avatar_url));
-                    _params
-                })
-                .collect();
-            CreateMany::new(
-                self.client._new_query_context(),
-                ::prisma_client_rust::QueryInfo::new("User", _outputs()),
-                data,
-            )
-        }
-        pub fn update(self, _where: UniqueWhereParam, _params: Vec<SetParam>) -> Update<'a> {
-            Update::new(
-                self.client._new_query_context(),
-                ::prisma_client_rust::QueryInfo::new("User", _outputs()),
-                _where.into(),
-                _params,
-                vec![],
-            )
-        }
-        pub fn update_many(
-            self,
-            _where: Vec<WhereParam>,
-            _params: Vec<SetParam>,
-        ) -> UpdateMany<'a> {
-            UpdateMany::new(
-                self.client._new_query_context(),
-                ::prisma_client_rust::QueryInfo::new("User", _outputs()),
-                _where,
-                _params,
-            )
-        }
-        pub fn upsert(
-            self,
-            _where: UniqueWhereParam,
-            (id, name, username, avatar_url, mut _params): (
-                i64,
-                String,
-                String,
-                String,
-                Vec<SetParam>,
-            ),
-            _update: Vec<SetParam>,
-        ) -> Upsert<'a> {
-            _params.push(id::set(id));
-            _params.push(name::set(name));
-            _params.push(username::set(username));
-            _params.push(avatar_url::set(avatar_url));
-            Upsert::new(
-                self.client._new_query_context(),
-                ::prisma_client_rust::QueryInfo::new("User", _outputs()),
-                _where.into(),
-                _params,
-                _update,
-            )
-        }
-        pub fn delete(self, _where: UniqueWhereParam) -> Delete<'a> {
-            Delete::new(
-                self.client._new_query_context(),
-                ::prisma_client_rust::QueryInfo::new("User", _outputs()),
-                _where.into(),
-                vec![],
-            )
-        }
-        pub fn delete_many(self, _where: Vec<WhereParam>) -> DeleteMany<'a> {
-            DeleteMany::new(
-                self.client._new_query_context(),
-                ::prisma_client_rust::QueryInfo::new("User", _outputs()),
-                _where.into(),
-            )
-        }
-        pub fn count(self, _where: Vec<WhereParam>) -> Count<'a> {
-            Count::new(
-                self.client._new_query_context(),
-                ::prisma_client_rust::QueryInfo::new("User", _outputs()),
-                vec![],
-            )
-        }
-    }
-}
-pub mod _prisma {
-    pub struct PrismaClient {
-        executor: ::prisma_client_rust::Executor,
-        query_schema: ::std::sync::Arc<::prisma_client_rust::schema::QuerySchema>,
-    }
-    impl ::std::fmt::Debug for PrismaClient {
-        fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
-            f.debug_struct("PrismaClient").finish()
-        }
-    }
-    impl PrismaClient {
-        pub(super) fn _new_query_context(&self) -> ::prisma_client_rust::queries::QueryContext {
-            ::prisma_client_rust::queries::QueryContext::new(&self.executor, &self.query_schema)
-        }
-        pub(super) fn _new(
-            executor: ::prisma_client_rust::Executor,
-            query_schema: std::sync::Arc<::prisma_client_rust::schema::QuerySchema>,
-        ) -> Self {
-            Self {
-                executor,
-                query_schema,
-            }
-        }
-        pub fn _query_raw<T: serde::de::DeserializeOwned>(
-            &self,
-            query: ::prisma_client_rust::raw::Raw,
-        ) -> ::prisma_client_rust::QueryRaw<T> {
-            ::prisma_client_rust::QueryRaw::new(
-                ::prisma_client_rust::queries::QueryContext::new(
-                    &self.executor,
-                    &self.query_schema,
-                ),
-                query,
-                super::DATABASE_STR,
-            )
-        }
-        pub fn _execute_raw(
-            &self,
-            query: ::prisma_client_rust::raw::Raw,
-        ) -> ::prisma_client_rust::ExecuteRaw {
-            ::prisma_client_rust::ExecuteRaw::new(
-                ::prisma_client_rust::queries::QueryContext::new(
-                    &self.executor,
-                    &self.query_schema,
-                ),
-                query,
-                super::DATABASE_STR,
-            )
-        }
-        pub async fn _batch<T: ::prisma_client_rust::BatchContainer<Marker>, Marker>(
-            &self,
-            queries: T,
-        ) -> ::prisma_client_rust::queries::Result<T::ReturnType> {
-            ::prisma_client_rust::batch(queries, &self.executor, &self.query_schema).await
-        }
-        pub fn user(&self) -> super::user::Actions {
-            super::user::Actions { client: &self }
-        }
-    }
-    #[derive(Debug, Clone, Copy, :: serde :: Serialize, :: serde :: Deserialize)]
-    pub enum QueryMode {
-        #[serde(rename = "default")]
-        Default,
-        #[serde(rename = "insensitive")]
-        Insensitive,
-    }
-    impl ToString for QueryMode {
-        fn to_string(&self) -> String {
-            match self {
-                Self::Default => "default".to_string(),
-                Self::Insensitive => "insensitive".to_string(),
-            }
-        }
-    }
-    #[derive(Debug, Clone, Copy, :: serde :: Serialize, :: serde :: Deserialize)]
-    pub enum SortOrder {
-        #[serde(rename = "asc")]
-        Asc,
-        #[serde(rename = "desc")]
-        Desc,
-    }
-    impl ToString for SortOrder {
-        fn to_string(&self) -> String {
-            match self {
-                Self::Asc => "asc".to_string(),
-                Self::Desc => "desc".to_string(),
-            }
-        }
-    }
-    #[derive(Debug, Clone, Copy, :: serde :: Serialize, :: serde :: Deserialize)]
-    pub enum TransactionIsolationLevel {
-        #[serde(rename = "ReadUncommitted")]
-        ReadUncommitted,
-        #[serde(rename = "ReadCommitted")]
-        ReadCommitted,
-        #[serde(rename = "RepeatableRead")]
-        RepeatableRead,
-        #[serde(rename = "Serializable")]
-        Serializable,
-    }
-    impl ToString for TransactionIsolationLevel {
-        fn to_string(&self) -> String {
-            match self {
-                Self::ReadUncommitted => "ReadUncommitted".to_string(),
-                Self::ReadCommitted => "ReadCommitted".to_string(),
-                Self::RepeatableRead => "RepeatableRead".to_string(),
-                Self::Serializable => "Serializable".to_string(),
-            }
-        }
-    }
-    #[derive(Debug, Clone, Copy, :: serde :: Serialize, :: serde :: Deserialize)]
-    pub enum UserScalarFieldEnum {
-        #[serde(rename = "id")]
-        Id,
-        #[serde(rename = "name")]
-        Name,
-        #[serde(rename = "username")]
-        Username,
-        #[serde(rename = "avatar_url")]
-        AvatarUrl,
-    }
-    impl ToString for UserScalarFieldEnum {
-        fn to_string(&self) -> String {
-            match self {
-                Self::Id => "id".to_string(),
-                Self::Name => "name".to_string(),
-                Self::Username => "username".to_string(),
-                Self::AvatarUrl => "avatar_url".to_string(),
-            }
-        }
-    }
-}
-pub use _prisma::PrismaClient;
@@ -0,0 +1,54 @@
+//! Logging in without any provider, to work on the registry offline.
+//!
+//! With `DEV_LOGIN=true`, `/login/dev?login=<name>` logs in as a fixture user
+//! named `name` (`dev` by default), created on first use like a provider login
+//! would. GitHub then doesn't need to be configured. Release builds refuse to
+//! start with it, see [`config`](crate::config).
+use rocket::State;
+use rocket::response::Redirect;
+use crate::Session;
+use crate::auth::{self, Profile, ProviderInfo};
+use crate::config::Config;
+use crate::db::prisma::PrismaClient;
+use crate::error::*;
+use crate::metrics::Metrics;
+use crate::sessions::{Redis, UserAgent};
+
+/// Prefix of the fixture users' identities.
+pub const NAME: &str = "dev";
+
+pub fn enabled(config: &Config) -> bool {
+    config.dev_login && cfg!(debug_assertions)
+}
+
+/// The login button, when enabled.
+pub fn info(config: &Config) -> Option<ProviderInfo> {
+    enabled(config).then(|| ProviderInfo {
+        name: NAME,
+        title: "Development".into(),
+        login_url: format!("/login/{NAME}")
+    })
+}
+
+fn is_valid(login: &str) -> bool {
+    !login.is_empty() && login.len() <= 39 && login.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+#[get("/login/dev?<login>")]
+pub async fn dev_login(login: Option<&str>, session: Session<'_>, redis: &State<Redis>, user_agent: UserAgent, client: &State<PrismaClient>, metrics: &State<Metrics>, config: &State<Config>) -> Result<Redirect, Error> {
+    if !enabled(config) {
+        return Err(Error::new(ErrorKind::NotFound, "Set DEV_LOGIN=true in a debug build", "Development login is disabled"));
+    }
+    let login = login.unwrap_or(NAME);
+    if !is_valid(login) {
+        return Err(Error::new(ErrorKind::ValidationError, "Use letters, digits and dashes", "Invalid login"));
+    }
+    let profile = Profile {
+        id: login.into(),
+        login: login.into(),
+        name: format!("{login} (development)"),
+        avatar_url: String::new()
+    };
+    let user = auth::sign_in(client, NAME, &profile).await?;
+    auth::start_session(session, redis, user_agent, metrics, user, NAME).await
+}
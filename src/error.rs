@@ -1,17 +1,170 @@
+use rocket::Request;
+use rocket::http::Status;
+use rocket::response::{self, content, status, Responder};
+use rocket::serde::de::{self, Deserializer};
+use rocket::serde::json;
+use rocket::serde::ser::Serializer;
 use rocket::serde::{Serialize, Deserialize};
+use crate::logging::{RequestError, RequestId};
 
-#[derive(Deserialize, Serialize)]
-#[serde(crate = "rocket::serde")]
 pub enum ErrorKind {
     NotLoggedIn,
+    Forbidden,
+    NotFound,
+    Conflict,
     ValidationError,
     GithubApiError,
-    DatabaseError(String)
+    /// A GitLab or Gitea instance failed to answer during login.
+    ProviderApiError,
+    RateLimited,
+    /// A dependency such as the database is down or isn't configured.
+    Unavailable,
+    /// Holds the underlying error, which is logged but never sent to clients.
+    DatabaseError(String),
+    /// The server failed on its own, e.g. a handler panicked.
+    Internal
 }
+
+impl ErrorKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErrorKind::NotLoggedIn => "NotLoggedIn",
+            ErrorKind::Forbidden => "Forbidden",
+            ErrorKind::NotFound => "NotFound",
+            ErrorKind::Conflict => "Conflict",
+            ErrorKind::ValidationError => "ValidationError",
+            ErrorKind::GithubApiError => "GithubApiError",
+            ErrorKind::ProviderApiError => "ProviderApiError",
+            ErrorKind::RateLimited => "RateLimited",
+            ErrorKind::Unavailable => "Unavailable",
+            ErrorKind::DatabaseError(_) => "DatabaseError",
+            ErrorKind::Internal => "Internal"
+        }
+    }
+
+    pub fn status(&self) -> Status {
+        match self {
+            ErrorKind::NotLoggedIn => Status::Unauthorized,
+            ErrorKind::Forbidden => Status::Forbidden,
+            ErrorKind::NotFound => Status::NotFound,
+            ErrorKind::Conflict => Status::Conflict,
+            ErrorKind::ValidationError => Status::UnprocessableEntity,
+            ErrorKind::RateLimited => Status::TooManyRequests,
+            ErrorKind::DatabaseError(_) | ErrorKind::Internal => Status::InternalServerError,
+            ErrorKind::GithubApiError | ErrorKind::ProviderApiError | ErrorKind::Unavailable => Status::ServiceUnavailable
+        }
+    }
+
+    /// The kind closest to an error status that no route produced itself.
+    pub fn of_status(status: Status) -> Self {
+        match status.code {
+            401 => ErrorKind::NotLoggedIn,
+            403 => ErrorKind::Forbidden,
+            404 => ErrorKind::NotFound,
+            409 => ErrorKind::Conflict,
+            429 => ErrorKind::RateLimited,
+            503 => ErrorKind::Unavailable,
+            500..=599 => ErrorKind::Internal,
+            _ => ErrorKind::ValidationError
+        }
+    }
+}
+
+impl Serialize for ErrorKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for ErrorKind {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match String::deserialize(deserializer)?.as_str() {
+            "NotLoggedIn" => ErrorKind::NotLoggedIn,
+            "Forbidden" => ErrorKind::Forbidden,
+            "NotFound" => ErrorKind::NotFound,
+            "Conflict" => ErrorKind::Conflict,
+            "ValidationError" => ErrorKind::ValidationError,
+            "GithubApiError" => ErrorKind::GithubApiError,
+            "ProviderApiError" => ErrorKind::ProviderApiError,
+            "RateLimited" => ErrorKind::RateLimited,
+            "Unavailable" => ErrorKind::Unavailable,
+            "DatabaseError" => ErrorKind::DatabaseError(String::new()),
+            "Internal" => ErrorKind::Internal,
+            other => return Err(de::Error::custom(format!("unknown error kind `{other}`")))
+        })
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 #[serde(crate = "rocket::serde")]
 pub struct Error {
     pub(crate) kind: ErrorKind,
     pub(crate) action: String,
     pub(crate) message: String
-}
\ No newline at end of file
+}
+
+impl Error {
+    pub fn new(kind: ErrorKind, action: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            action: action.into(),
+            message: message.into()
+        }
+    }
+
+    pub fn not_logged_in() -> Self {
+        Self::new(ErrorKind::NotLoggedIn, "Send a `token` cookie.", "Unauthorized")
+    }
+
+    pub fn database(err: impl ToString) -> Self {
+        Self::new(ErrorKind::DatabaseError(err.to_string()), "Try again", "Database query failed")
+    }
+
+    pub fn status(&self) -> Status {
+        self.kind.status()
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let ErrorKind::DatabaseError(err) = &self.kind {
+            write!(f, " ({err})")?;
+        }
+        write!(f, ". {}", self.action)
+    }
+}
+
+/// Error body sent to clients, with the ID of the request.
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+struct Body<'a> {
+    #[serde(flatten)]
+    error: &'a Error,
+    request_id: &'a str
+}
+
+/// Responds with the status of the error kind and the error as JSON. The full
+/// error, including database details that never reach the client, goes to the
+/// request's log line.
+impl<'r> Responder<'r, 'static> for Error {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        request.local_cache(|| RequestError(Some(self.to_string())));
+        let body = json::to_string(&Body { error: &self, request_id: RequestId::of(request) })
+            .map_err(|_| Status::InternalServerError)?;
+        status::Custom(self.status(), content::RawJson(body)).respond_to(request)
+    }
+}
+
+/// Answers every error under `/api/` that no route or more specific catcher
+/// handled, e.g. malformed JSON or an unknown path, with an `Error` body. The
+/// status stays the one Rocket chose.
+#[catch(default)]
+pub fn api_error(status: Status, _: &Request<'_>) -> status::Custom<Error> {
+    let action = match status.code {
+        404 => "Check the URL against /api/openapi.json",
+        400..=499 => "Check the request against /api/openapi.json",
+        _ => "Try again later"
+    };
+    status::Custom(status, Error::new(ErrorKind::of_status(status), action, status.reason().unwrap_or("Error")))
+}
@@ -0,0 +1,74 @@
+//! Logging in with a Gitea or Forgejo instance, Codeberg unless `GITEA_URL`
+//! says otherwise.
+//!
+//! Gitea has no API to revoke a token, so its tokens are left to expire.
+use rocket::State;
+use rocket::http::CookieJar;
+use rocket::response::Redirect;
+use rocket::serde::Deserialize;
+use rocket_oauth2::{OAuth2, OAuthConfig, StaticProvider, TokenResponse};
+use crate::Session;
+use crate::auth::{self, Profile, Provider};
+use crate::config::Config;
+use crate::db::prisma::PrismaClient;
+use crate::error::*;
+use crate::metrics::Metrics;
+use crate::rate_limit::{Login, RateLimit};
+use crate::sessions::{Redis, UserAgent};
+pub struct Gitea;
+
+/// The fields we use of `GET /api/v1/user`.
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+struct GiteaUser {
+    id: i64,
+    login: String,
+    #[serde(default)]
+    full_name: String,
+    #[serde(default)]
+    avatar_url: String
+}
+
+#[rocket::async_trait]
+impl Provider for Gitea {
+    const NAME: &'static str = "gitea";
+    const SCOPES: &'static [&'static str] = &["read:user"];
+
+    fn title(config: &Config) -> String {
+        config.gitea_name.clone()
+    }
+
+    fn oauth_config(config: &Config) -> Option<OAuthConfig> {
+        config.gitea.as_ref().map(|app| OAuthConfig::new(
+            StaticProvider {
+                auth_uri: format!("{}/login/oauth/authorize", app.url).into(),
+                token_uri: format!("{}/login/oauth/access_token", app.url).into()
+            },
+            app.client_id.clone(),
+            app.client_secret.clone(),
+            Some(app.redirect_url.clone()),
+        ))
+    }
+
+    async fn profile(config: &Config, token: &str) -> Result<Profile, String> {
+        let app = config.gitea.as_ref().ok_or("Gitea isn't configured")?;
+        let user: GiteaUser = auth::fetch_json(&format!("{}/api/v1/user", app.url), token).await?;
+        let name = if user.full_name.is_empty() { user.login.clone() } else { user.full_name };
+        Ok(Profile {
+            id: user.id.to_string(),
+            login: user.login,
+            name,
+            avatar_url: user.avatar_url
+        })
+    }
+}
+
+#[get("/login/gitea?<link>")]
+pub async fn gitea_login(_limit: RateLimit<Login>, oauth2: Option<OAuth2<Gitea>>, cookies: &CookieJar<'_>, config: &State<Config>, session: Session<'_>, link: bool) -> Result<Redirect, Error> {
+    auth::login(oauth2, cookies, config, session, link).await
+}
+
+#[get("/auth/gitea")]
+pub async fn gitea_callback(_limit: RateLimit<Login>, token: TokenResponse<Gitea>, session: Session<'_>, redis: &State<Redis>, user_agent: UserAgent, client: &State<PrismaClient>, metrics: &State<Metrics>, config: &State<Config>) -> Result<Redirect, Error> {
+    auth::callback(token, session, redis, user_agent, client, metrics, config).await
+}
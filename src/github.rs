@@ -1,70 +1,84 @@
-use rocket::http::{CookieJar, Status};
+use rocket::State;
+use rocket::http::CookieJar;
 use rocket::response::Redirect;
-use rocket::serde::json::Json;
-use rocket_oauth2::{OAuth2, TokenResponse};
+use rocket::serde::json::json;
+use rocket_oauth2::{OAuth2, OAuthConfig, StaticProvider, TokenResponse};
 use octorust::Client;
 use octorust::auth::Credentials;
-use rocket::response::status;
-use crate::{Session, SessionInfo};
-use crate::db::*;
+use crate::Session;
+use crate::auth::{self, Profile, Provider};
+use crate::config::Config;
+use crate::db::prisma::PrismaClient;
 use crate::error::*;
+use crate::metrics::Metrics;
+use crate::rate_limit::{Login, RateLimit};
+use crate::sessions::{Redis, UserAgent};
 pub struct GitHub;
 
-#[get("/login/github")]
-pub fn github_login(oauth2: OAuth2<GitHub>, cookies: &CookieJar<'_>) -> Redirect {
-    oauth2.get_redirect(cookies, &["read:user"]).unwrap()
+#[rocket::async_trait]
+impl Provider for GitHub {
+    const NAME: &'static str = "github";
+    const SCOPES: &'static [&'static str] = &["read:user"];
+
+    fn title(_: &Config) -> String {
+        "GitHub".into()
+    }
+
+    /// Always configured, except with `DEV_LOGIN` where it may be left out.
+    fn oauth_config(config: &Config) -> Option<OAuthConfig> {
+        if config.gh_client_id.is_empty() {
+            return None;
+        }
+        Some(OAuthConfig::new(
+            StaticProvider {
+                auth_uri: format!("{}/login/oauth/authorize", config.gh_url).into(),
+                token_uri: format!("{}/login/oauth/access_token", config.gh_url).into()
+            },
+            config.gh_client_id.clone(),
+            config.gh_client_secret.clone(),
+            Some(config.gh_redirect_url.clone()),
+        ))
+    }
+
+    async fn profile(config: &Config, token: &str) -> Result<Profile, String> {
+        let mut github = Client::new("LapceExtensions", Credentials::Token(token.to_string()))
+            .map_err(|err| err.to_string())?;
+        github.with_host_override(&config.gh_api_url);
+        let user = github.users().get_authenticated().await.map_err(|err| err.to_string())?;
+        let user = user.public_user().ok_or("the authenticated user isn't a public user")?;
+        Ok(Profile {
+            id: user.id.to_string(),
+            login: user.login.clone(),
+            name: user.name.clone(),
+            avatar_url: user.avatar_url.clone()
+        })
+    }
+
+    async fn revoke(config: &Config, token: &str) -> Result<(), String> {
+        reqwest::Client::new()
+            .delete(format!("{}/applications/{}/token", config.gh_api_url, config.gh_client_id))
+            .basic_auth(&config.gh_client_id, Some(&config.gh_client_secret))
+            .header("User-Agent", "LapceExtensions")
+            .header("Accept", "application/vnd.github+json")
+            .json(&json!({ "access_token": token }))
+            .send().await
+            .and_then(|response| response.error_for_status())
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+    }
+
+    fn api_error() -> ErrorKind {
+        ErrorKind::GithubApiError
+    }
+}
+
+#[get("/login/github?<link>")]
+pub async fn github_login(_limit: RateLimit<Login>, oauth2: Option<OAuth2<GitHub>>, cookies: &CookieJar<'_>, config: &State<Config>, session: Session<'_>, link: bool) -> Result<Redirect, Error> {
+    auth::login(oauth2, cookies, config, session, link).await
 }
 
 #[get("/auth/github")]
-pub async fn github_callback(token: TokenResponse<GitHub>, session: Session<'_>) -> Result<Redirect, status::Custom<Json<Error>>>
+pub async fn github_callback(_limit: RateLimit<Login>, token: TokenResponse<GitHub>, session: Session<'_>, redis: &State<Redis>, user_agent: UserAgent, client: &State<PrismaClient>, metrics: &State<Metrics>, config: &State<Config>) -> Result<Redirect, Error>
 {
-    let gh_token =token.access_token().to_string();
-    let github = Client::new("LapceExtensions", Credentials::Token(gh_token.clone().into()));
-    match github {
-        Ok(github) => {
-            let users = github.users();
-            let user = users.get_authenticated().await.unwrap();
-            let user = user.public_user().unwrap();
-            if let Err(err) = session.set(SessionInfo {
-                gh_token,
-                id: user.id as u64
-            }).await {
-                Err(status::Custom(Status::InternalServerError, Json(Error {
-                    kind: ErrorKind::DatabaseError(err.to_string()),
-                    action: "Try again".into(),
-                    message: "Can't set session on redis db".into()
-                })))
-            } else {
-                let client = establish_connection().await;
-                match client {
-                    Ok(client) => {
-                        client.user().upsert(
-                            prisma::user::id::equals(user.id),
-                            prisma::user::create(
-                                user.id,
-                                user.login.clone(),
-                                user.name.clone(),
-                                user.avatar_url.clone(),
-                                vec![]
-                            ), 
-                            vec![
-                                prisma::user::name::set(user.login.clone()),
-                                prisma::user::username::set(user.name.clone()),
-                                prisma::user::avatar_url::set(user.avatar_url.clone()),
-                            ]
-                        ).exec().await.unwrap();
-                        Ok(Redirect::to("/"))
-                    }
-                    Err(err) => {
-                        Err(status::Custom(Status::InternalServerError, Json(err)))
-                    }
-                }
-            }
-        }
-        Err(_) => Err(status::Custom(Status::InternalServerError, Json(Error {
-            kind: ErrorKind::GithubApiError,
-            action: "Try again".into(),
-            message: "Can't fetch from github api".into()
-        })))
-    }
+    auth::callback(token, session, redis, user_agent, client, metrics, config).await
 }
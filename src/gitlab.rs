@@ -0,0 +1,80 @@
+//! Logging in with GitLab.com or a self-hosted GitLab, see `GITLAB_*` in
+//! [`config`](crate::config).
+use rocket::State;
+use rocket::http::CookieJar;
+use rocket::response::Redirect;
+use rocket::serde::Deserialize;
+use rocket_oauth2::{OAuth2, OAuthConfig, StaticProvider, TokenResponse};
+use crate::Session;
+use crate::auth::{self, Profile, Provider};
+use crate::config::Config;
+use crate::db::prisma::PrismaClient;
+use crate::error::*;
+use crate::metrics::Metrics;
+use crate::rate_limit::{Login, RateLimit};
+use crate::sessions::{Redis, UserAgent};
+pub struct GitLab;
+
+/// The fields we use of `GET /api/v4/user`.
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+struct GitLabUser {
+    id: i64,
+    username: String,
+    name: String,
+    avatar_url: Option<String>
+}
+
+#[rocket::async_trait]
+impl Provider for GitLab {
+    const NAME: &'static str = "gitlab";
+    const SCOPES: &'static [&'static str] = &["read_user"];
+
+    fn title(_: &Config) -> String {
+        "GitLab".into()
+    }
+
+    fn oauth_config(config: &Config) -> Option<OAuthConfig> {
+        config.gitlab.as_ref().map(|app| OAuthConfig::new(
+            StaticProvider {
+                auth_uri: format!("{}/oauth/authorize", app.url).into(),
+                token_uri: format!("{}/oauth/token", app.url).into()
+            },
+            app.client_id.clone(),
+            app.client_secret.clone(),
+            Some(app.redirect_url.clone()),
+        ))
+    }
+
+    async fn profile(config: &Config, token: &str) -> Result<Profile, String> {
+        let app = config.gitlab.as_ref().ok_or("GitLab isn't configured")?;
+        let user: GitLabUser = auth::fetch_json(&format!("{}/api/v4/user", app.url), token).await?;
+        Ok(Profile {
+            id: user.id.to_string(),
+            login: user.username,
+            name: user.name,
+            avatar_url: user.avatar_url.unwrap_or_default()
+        })
+    }
+
+    async fn revoke(config: &Config, token: &str) -> Result<(), String> {
+        let app = config.gitlab.as_ref().ok_or("GitLab isn't configured")?;
+        reqwest::Client::new()
+            .post(format!("{}/oauth/revoke", app.url))
+            .form(&[("client_id", app.client_id.as_str()), ("client_secret", app.client_secret.as_str()), ("token", token)])
+            .send().await
+            .and_then(|response| response.error_for_status())
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+    }
+}
+
+#[get("/login/gitlab?<link>")]
+pub async fn gitlab_login(_limit: RateLimit<Login>, oauth2: Option<OAuth2<GitLab>>, cookies: &CookieJar<'_>, config: &State<Config>, session: Session<'_>, link: bool) -> Result<Redirect, Error> {
+    auth::login(oauth2, cookies, config, session, link).await
+}
+
+#[get("/auth/gitlab")]
+pub async fn gitlab_callback(_limit: RateLimit<Login>, token: TokenResponse<GitLab>, session: Session<'_>, redis: &State<Redis>, user_agent: UserAgent, client: &State<PrismaClient>, metrics: &State<Metrics>, config: &State<Config>) -> Result<Redirect, Error> {
+    auth::callback(token, session, redis, user_agent, client, metrics, config).await
+}
@@ -0,0 +1,96 @@
+//! Probes for the platform running the registry.
+//!
+//! `/healthz` only says the process answers; `/readyz` checks every dependency
+//! a request may need and answers 503 until they're all reachable.
+use std::time::Instant;
+use prisma_client_rust::chrono::{TimeZone, Utc};
+use rocket::State;
+use rocket::http::Status;
+use rocket::response::status;
+use rocket::serde::json::Json;
+use rocket::serde::Serialize;
+use rocket::tokio::fs;
+use crate::config::{Config, Storage};
+use crate::db::prisma::PrismaClient;
+use crate::sessions::Redis;
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Check {
+    pub ok: bool,
+    pub latency_ms: u64,
+    /// Short description of the failure. Details only go to the server log.
+    pub error: Option<&'static str>
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Readiness {
+    pub ready: bool,
+    pub postgres: Check,
+    pub redis: Check,
+    pub storage: Check
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Version {
+    pub version: &'static str,
+    pub git_sha: &'static str,
+    pub built_at: String
+}
+
+/// Times `probe` and logs why it failed, if it did.
+async fn check<E: std::fmt::Display>(name: &str, error: &'static str, probe: impl std::future::Future<Output = Result<(), E>>) -> Check {
+    let start = Instant::now();
+    let result = probe.await;
+    let latency_ms = start.elapsed().as_millis() as u64;
+    if let Err(err) = &result {
+        warn!("Readiness check {} failed: {}", name, err);
+    }
+    Check { ok: result.is_ok(), latency_ms, error: result.err().map(|_| error) }
+}
+
+/// Writes and removes a probe file, as the permission bits don't tell whether
+/// this process can write, e.g. on a read-only mount.
+async fn storage_writable(config: &Config) -> std::io::Result<()> {
+    match config.storage {
+        Storage::Filesystem => {
+            let probe = config.storage_path.join(format!(".readyz-{:016x}", rand::random::<u64>()));
+            fs::write(&probe, b"ok").await?;
+            fs::remove_file(&probe).await
+        }
+    }
+}
+
+#[get("/healthz")]
+pub fn healthz() -> &'static str {
+    "ok"
+}
+
+#[get("/readyz")]
+pub async fn readyz(client: &State<PrismaClient>, redis: &State<Redis>, config: &State<Config>) -> status::Custom<Json<Readiness>> {
+    let postgres = check("postgres", "Database unreachable", async {
+        client.user().find_first(vec![]).exec().await.map(|_| ())
+    }).await;
+    let redis = check("redis", "Session store unreachable", async {
+        redis::cmd("PING").query_async::<_, String>(&mut redis.0.clone()).await.map(|_| ())
+    }).await;
+    let storage = check("storage", "Storage isn't writable", storage_writable(config)).await;
+    let ready = postgres.ok && redis.ok && storage.ok;
+    let status = if ready { Status::Ok } else { Status::ServiceUnavailable };
+    status::Custom(status, Json(Readiness { ready, postgres, redis, storage }))
+}
+
+#[get("/version")]
+pub fn version() -> Json<Version> {
+    let built_at = env!("BUILD_TIMESTAMP").parse().ok()
+        .and_then(|secs| Utc.timestamp_opt(secs, 0).single())
+        .map(|time| time.to_rfc3339())
+        .unwrap_or_default();
+    Json(Version {
+        version: env!("CARGO_PKG_VERSION"),
+        git_sha: env!("GIT_SHA"),
+        built_at
+    })
+}
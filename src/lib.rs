@@ -0,0 +1,232 @@
+#[macro_use] extern crate rocket;
+pub mod user;
+pub mod auth;
+mod github;
+mod gitlab;
+mod gitea;
+mod dev;
+pub mod db;
+pub mod error;
+pub mod signing;
+pub mod transparency;
+pub mod archive;
+pub mod moderation;
+pub mod reports;
+pub mod sessions;
+pub mod csrf;
+pub mod rate_limit;
+pub mod config;
+pub mod health;
+pub mod metrics;
+pub mod logging;
+pub mod openapi;
+use dotenvy::dotenv;
+use redis::Client;
+use std::ops::Deref;
+use rocket::{Build, Request, Rocket};
+use rocket::http::Status;
+use rocket::outcome::Outcome;
+use rocket::request::{self, FromRequest};
+use rocket::fairing::AdHoc;
+use rocket::figment::Figment;
+use rocket::fs::{FileServer, Options};
+use rocket::serde::{Deserialize, Serialize};
+pub use rocket_session_store::{redis::*, SessionStore, CookieConfig};
+use crate::config::{Config, Storage};
+use crate::github::{github_callback, github_login, GitHub};
+use crate::gitea::{gitea_callback, gitea_login, Gitea};
+use crate::gitlab::{gitlab_callback, gitlab_login, GitLab};
+use crate::user::get_user;
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct SessionInfo {
+    /// The registry user, whatever provider they logged in with.
+    pub id: u64,
+    /// [`auth::Provider::NAME`] of the provider the session was opened with.
+    #[serde(default = "github_provider")]
+    pub provider: String,
+    /// Identifies the session in `sessions:<id>`, see [`sessions`].
+    #[serde(default)]
+    pub sid: String,
+    /// Provider whose identity is being linked to the user, see [`auth::login`].
+    #[serde(default)]
+    pub linking: Option<String>
+}
+/// Sessions opened before there were other providers.
+fn github_provider() -> String {
+    <GitHub as auth::Provider>::NAME.into()
+}
+/// Name of the session cookie.
+pub const SESSION_COOKIE: &str = "token";
+
+/// The session of the request, refused with 403 on a cross-site request that
+/// changes data (see [`csrf`]).
+pub struct Session<'s>(rocket_session_store::Session<'s, SessionInfo>);
+
+impl<'s> Deref for Session<'s> {
+    type Target = rocket_session_store::Session<'s, SessionInfo>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Session<'r> {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        if !csrf::is_trusted(request, SESSION_COOKIE) {
+            return Outcome::Failure((Status::Forbidden, ()));
+        }
+        match request.guard::<rocket_session_store::Session<'r, SessionInfo>>().await {
+            Outcome::Success(session) => Outcome::Success(Session(session)),
+            Outcome::Failure((status, _)) => Outcome::Failure((status, ())),
+            Outcome::Forward(forward) => Outcome::Forward(forward)
+        }
+    }
+}
+fn session_store(client: Client) -> SessionStore<SessionInfo> {
+    let redis_store: RedisStore<SessionInfo> = RedisStore::new(client);
+	SessionStore {
+		store: Box::new(redis_store),
+		name: SESSION_COOKIE.into(),
+
+		duration: sessions::SESSION_DURATION,
+		cookie: CookieConfig {
+            http_only: false,
+            secure: true,
+            path: Some("/".into()),
+            ..Default::default()
+        },
+	}
+}
+pub fn rocket() -> Rocket<Build> {
+    dotenv().ok();
+    build(config::figment())
+}
+/// The registry configured from `figment`, see [`config`].
+pub fn build(figment: Figment) -> Rocket<Build> {
+    let metrics = metrics::Metrics::new().expect("Failed to register metrics");
+    rocket::custom(figment)
+        .mount("/", routes![
+            github_callback,
+            github_login,
+            gitlab_callback,
+            gitlab_login,
+            gitea_callback,
+            gitea_login,
+            dev::dev_login,
+        ])
+        .mount("/", routes![health::healthz, health::readyz, health::version, metrics::metrics])
+        .mount("/api/", routes![
+            get_user,
+            crate::user::logout,
+            crate::user::get_public_user,
+            crate::user::export_data,
+            crate::user::delete_account,
+        ])
+        .mount("/api/", routes![signing::list_keys, signing::add_key, signing::remove_key])
+        .mount("/api/", routes![
+            transparency::log_key,
+            transparency::tree_head,
+            transparency::inclusion_proof,
+            transparency::consistency_proof,
+        ])
+        .mount("/api/", routes![
+            moderation::list_actions,
+            moderation::ban_user,
+            moderation::unban_user,
+            moderation::set_role,
+            moderation::list_locked_names,
+            moderation::lock_name,
+            moderation::unlock_name,
+        ])
+        .mount("/api/", routes![
+            reports::create_report,
+            reports::own_reports,
+            reports::report_queue,
+            reports::triage_report,
+        ])
+        .mount("/api/", routes![
+            sessions::list_sessions,
+            sessions::revoke_session,
+            sessions::revoke_all_sessions,
+        ])
+        .mount("/api/", routes![auth::providers, auth::list_identities, auth::unlink_identity])
+        .mount("/api/", routes![openapi::openapi, openapi::docs, openapi::docs_style, openapi::docs_script])
+        .attach(AdHoc::try_on_ignite("Configuration", |rocket| async {
+            match Config::from_figment(rocket.figment()) {
+                Ok(config) => {
+                    let signer = transparency::LogSigner::from_config(&config);
+                    let limits = rate_limit::RateLimits::from_config(&config);
+                    Ok(rocket.manage(signer).manage(limits).manage(config))
+                }
+                Err(err) => {
+                    error!("{}", err);
+                    Err(rocket)
+                }
+            }
+        }))
+        .attach(AdHoc::try_on_ignite("Storage", |rocket| async {
+            let path = match rocket.state::<Config>().map(|config| (config.storage, config.storage_path.clone())) {
+                Some((Storage::Filesystem, path)) => path,
+                None => return Err(rocket)
+            };
+            match rocket::tokio::fs::create_dir_all(&path).await {
+                Ok(()) => Ok(rocket),
+                Err(err) => {
+                    error!("Can't create the storage directory {}: {}", path.display(), err);
+                    Err(rocket)
+                }
+            }
+        }))
+        .attach(auth::fairing::<GitHub>())
+        .attach(auth::fairing::<GitLab>())
+        .attach(auth::fairing::<Gitea>())
+        .attach(AdHoc::try_on_ignite("Database", |rocket| async {
+            let url = match rocket.state::<Config>() {
+                Some(config) => config.database_url.clone(),
+                None => return Err(rocket)
+            };
+            match db::establish_connection(&url).await {
+                Ok(client) => Ok(rocket.manage(client)),
+                Err(err) => {
+                    error!("{}", err);
+                    Err(rocket)
+                }
+            }
+        }))
+        .attach(AdHoc::try_on_ignite("Redis", |rocket| async {
+            let client = match rocket.state::<Config>().map(|config| Client::open(config.redis_url.as_str())) {
+                Some(Ok(client)) => client,
+                Some(Err(err)) => {
+                    error!("Invalid REDIS_URL: {}", err);
+                    return Err(rocket);
+                }
+                None => return Err(rocket)
+            };
+            match redis::aio::ConnectionManager::new(client.clone()).await {
+                Ok(manager) => Ok(rocket
+                    .manage(sessions::Redis(manager))
+                    .attach(session_store(client).fairing())),
+                Err(err) => {
+                    error!("Failed to connect to redis: {}", err);
+                    Err(rocket)
+                }
+            }
+        }))
+        .attach(AdHoc::on_ignite("OpenAPI", |rocket| async {
+            let document = openapi::OpenApi::from_rocket(&rocket);
+            rocket.manage(document)
+        }))
+        .attach(logging::RequestLogger)
+        .attach(sessions::SessionTracker)
+        .manage(transparency::LogCache::default())
+        .manage(metrics.clone())
+        .attach(metrics)
+        .register("/api/", catchers![csrf::forbidden, rate_limit::too_many_requests, error::api_error])
+        .register("/", catchers![rate_limit::too_many_requests])
+        // The frontend is built separately and isn't there in tests.
+        .mount("/", FileServer::new("marketplace/dist", Options::Index | Options::Missing))
+}
@@ -0,0 +1,107 @@
+//! Request IDs and one JSON log line per request.
+//!
+//! Each request gets the ID sent in `X-Request-Id` when it looks sane, or a
+//! random one. It's echoed in the response header and in every error body, so
+//! a user's bug report can be matched with the log line:
+//!
+//! ```text
+//! {"request_id":"…","method":"GET","route":"/api/user","path":"/api/user","status":200,"user_id":1,"latency_ms":3}
+//! ```
+//!
+//! Lines are written to stdout regardless of Rocket's log level.
+use std::time::Instant;
+use rand::Rng;
+use rocket::{Data, Request, Response};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::Header;
+use rocket::serde::Serialize;
+use rocket::serde::json;
+
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+#[derive(Clone, Debug)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    /// The ID of `request`, generating one if the fairing didn't run.
+    pub fn of<'r>(request: &'r Request<'_>) -> &'r str {
+        &request.local_cache(|| RequestId(generate())).0
+    }
+}
+
+/// The user the request was made for, cached by
+/// [`SessionTracker`](crate::sessions::SessionTracker).
+#[derive(Clone, Copy, Debug)]
+pub struct RequestUser(pub Option<u64>);
+
+/// Description of the error the request failed with, cached by the `Error`
+/// responder.
+#[derive(Clone, Debug)]
+pub struct RequestError(pub Option<String>);
+
+#[derive(Clone, Copy)]
+struct RequestStart(Instant);
+
+fn generate() -> String {
+    hex::encode(rand::thread_rng().gen::<[u8; 16]>())
+}
+
+fn is_valid(id: &str) -> bool {
+    !id.is_empty() && id.len() <= 64 && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+struct LogLine<'a> {
+    request_id: &'a str,
+    method: &'a str,
+    route: Option<String>,
+    path: String,
+    status: u16,
+    user_id: Option<u64>,
+    latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'a str>
+}
+
+pub struct RequestLogger;
+
+#[rocket::async_trait]
+impl Fairing for RequestLogger {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request logger",
+            kind: Kind::Request | Kind::Response
+        }
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        let id = request.headers().get_one(REQUEST_ID_HEADER)
+            .filter(|id| is_valid(id))
+            .map(String::from)
+            .unwrap_or_else(generate);
+        request.local_cache(|| RequestId(id));
+        request.local_cache(|| RequestStart(Instant::now()));
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let id = RequestId::of(request);
+        let RequestStart(start) = *request.local_cache(|| RequestStart(Instant::now()));
+        let RequestUser(user_id) = *request.local_cache(|| RequestUser(None));
+        let RequestError(error) = request.local_cache(|| RequestError(None));
+        let line = LogLine {
+            request_id: id,
+            method: request.method().as_str(),
+            route: request.route().map(|route| route.uri.to_string()),
+            path: request.uri().path().to_string(),
+            status: response.status().code,
+            user_id,
+            latency_ms: start.elapsed().as_millis() as u64,
+            error: error.as_deref()
+        };
+        if let Ok(line) = json::to_string(&line) {
+            println!("{line}");
+        }
+        response.set_header(Header::new(REQUEST_ID_HEADER, id.to_string()));
+    }
+}
@@ -0,0 +1,111 @@
+//! Prometheus metrics, served at `/metrics`.
+//!
+//! The [`Metrics`] fairing counts every request and its latency by route
+//! pattern and status; routes bump the domain counters through
+//! `&State<Metrics>`. When `METRICS_TOKEN` is set, scrapers must send it as a
+//! bearer token.
+use std::time::Instant;
+use prometheus::{Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, Opts, Registry, TextEncoder};
+use rocket::{Data, Request, Response, State};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::ContentType;
+use rocket::outcome::Outcome;
+use rocket::request::{self, FromRequest};
+use crate::config::Config;
+use crate::error::*;
+
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Registry,
+    requests: IntCounterVec,
+    latency: HistogramVec,
+    pub logins: IntCounter,
+    /// Failed calls to an identity provider's API, by provider.
+    pub provider_api_failures: IntCounterVec
+}
+
+impl Metrics {
+    pub fn new() -> prometheus::Result<Self> {
+        let registry = Registry::new_custom(Some("registry".into()), None)?;
+        let requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests by route and status"),
+            &["method", "route", "status"]
+        )?;
+        let latency = HistogramVec::new(
+            HistogramOpts::new("http_request_duration_seconds", "HTTP request latency by route"),
+            &["method", "route"]
+        )?;
+        let logins = IntCounter::new("logins_total", "Successful logins")?;
+        let provider_api_failures = IntCounterVec::new(
+            Opts::new("provider_api_failures_total", "Failed calls to the API of an identity provider"),
+            &["provider"]
+        )?;
+        registry.register(Box::new(requests.clone()))?;
+        registry.register(Box::new(latency.clone()))?;
+        registry.register(Box::new(logins.clone()))?;
+        registry.register(Box::new(provider_api_failures.clone()))?;
+        Ok(Self { registry, requests, latency, logins, provider_api_failures })
+    }
+}
+
+/// When the request started, stored by the fairing.
+#[derive(Clone, Copy)]
+struct RequestStart(Option<Instant>);
+
+#[rocket::async_trait]
+impl Fairing for Metrics {
+    fn info(&self) -> Info {
+        Info {
+            name: "Prometheus metrics",
+            kind: Kind::Request | Kind::Response
+        }
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        request.local_cache(|| RequestStart(Some(Instant::now())));
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        // The route pattern rather than the path, so ids don't blow up the
+        // number of series.
+        let route = request.route().map(|route| route.uri.to_string()).unwrap_or_else(|| "unmatched".into());
+        let method = request.method().as_str();
+        self.requests
+            .with_label_values(&[method, &route, &response.status().code.to_string()])
+            .inc();
+        if let RequestStart(Some(start)) = request.local_cache(|| RequestStart(None)) {
+            self.latency
+                .with_label_values(&[method, &route])
+                .observe(start.elapsed().as_secs_f64());
+        }
+    }
+}
+
+/// Passes if `METRICS_TOKEN` isn't set, or the request sends it.
+pub struct Scraper;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Scraper {
+    type Error = Error;
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let expected = request.rocket().state::<Config>().and_then(|config| config.metrics_token.as_deref());
+        let sent = request.headers().get_one("Authorization").and_then(|value| value.strip_prefix("Bearer "));
+        match expected {
+            Some(expected) if sent != Some(expected) => {
+                let err = Error::new(ErrorKind::NotLoggedIn, "Send the metrics token as a bearer token", "Unauthorized");
+                Outcome::Failure((err.status(), err))
+            }
+            _ => Outcome::Success(Scraper)
+        }
+    }
+}
+
+#[get("/metrics")]
+pub fn metrics(scraper: Result<Scraper, Error>, metrics: &State<Metrics>) -> Result<(ContentType, Vec<u8>), Error> {
+    scraper?;
+    let mut buffer = vec![];
+    TextEncoder::new().encode(&metrics.registry.gather(), &mut buffer)
+        .map_err(|err| Error::new(ErrorKind::Unavailable, "Try again", format!("Can't encode metrics: {err}")))?;
+    Ok((ContentType::Plain, buffer))
+}
@@ -0,0 +1,246 @@
+//! Roles, request guards for staff members and the moderation API.
+//!
+//! Every moderation route records the acting user and a reason in
+//! `ModerationAction`, in the same `_batch` as the change itself so neither
+//! happens without the other.
+use prisma_client_rust::Direction;
+use prisma_client_rust::chrono::Utc;
+use rocket::State;
+use rocket::http::Status;
+use rocket::outcome::Outcome;
+use rocket::request::{self, FromRequest, Request};
+use rocket::serde::json::Json;
+use rocket::serde::Deserialize;
+use crate::Session;
+use crate::db::prisma::{locked_name, moderation_action, user, PrismaClient, Role};
+use crate::error::*;
+use crate::sessions::{self, Redis};
+
+pub fn rank(role: &Role) -> u8 {
+    match role {
+        Role::User => 0,
+        Role::Moderator => 1,
+        Role::Admin => 2
+    }
+}
+
+/// Any logged in user who isn't banned.
+pub struct Member(pub user::Data);
+
+/// A logged in, non banned user with at least the moderator role.
+pub struct Moderator(pub user::Data);
+
+/// A logged in, non banned user with the admin role.
+pub struct Admin(pub user::Data);
+
+async fn find_user(request: &Request<'_>, required: Role) -> Result<user::Data, Error> {
+    let session = match request.guard::<Session<'_>>().await {
+        Outcome::Success(session) => session,
+        Outcome::Failure((Status::Forbidden, _)) => return Err(crate::csrf::cross_site_error()),
+        _ => return Err(Error::not_logged_in())
+    };
+    let session = session.get().await.ok().flatten().ok_or_else(Error::not_logged_in)?;
+    let client = request.rocket().state::<PrismaClient>()
+        .ok_or_else(|| Error::new(ErrorKind::Unavailable, "Try again later", "The database isn't available"))?;
+    let user = client.user()
+        .find_unique(user::id::equals(session.id as i64))
+        .exec().await
+        .map_err(Error::database)?
+        .ok_or_else(Error::not_logged_in)?;
+    if user.banned_at.is_some() {
+        return Err(Error::new(ErrorKind::Forbidden, "Contact the registry moderators", "This account is banned"));
+    }
+    if rank(&user.role) < rank(&required) {
+        return Err(Error::new(ErrorKind::Forbidden, "Ask an administrator for access", "You don't have the required role"));
+    }
+    Ok(user)
+}
+
+async fn user_with_role(request: &Request<'_>, required: Role) -> request::Outcome<user::Data, Error> {
+    match find_user(request, required).await {
+        Ok(user) => Outcome::Success(user),
+        Err(err) => Outcome::Failure((err.status(), err))
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Member {
+    type Error = Error;
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        user_with_role(request, Role::User).await.map(Member)
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Moderator {
+    type Error = Error;
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        user_with_role(request, Role::Moderator).await.map(Moderator)
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Admin {
+    type Error = Error;
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        user_with_role(request, Role::Admin).await.map(Admin)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Reason {
+    pub reason: String
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RoleChange {
+    pub role: Role,
+    pub reason: String
+}
+
+pub(crate) fn require_reason(reason: &str) -> Result<(), Error> {
+    if reason.trim().is_empty() {
+        return Err(Error::new(ErrorKind::ValidationError, "Explain why in `reason`", "A reason is required"));
+    }
+    Ok(())
+}
+
+/// Fetches the target of a moderation action, refusing to act on users with
+/// a role at least as high as the actor's.
+async fn target_user(client: &PrismaClient, actor: &user::Data, id: i64) -> Result<user::Data, Error> {
+    let target = client.user()
+        .find_unique(user::id::equals(id))
+        .exec().await
+        .map_err(Error::database)?
+        .ok_or_else(|| Error::new(ErrorKind::NotFound, "Check the user id", "There's no user with this id"))?;
+    if rank(&target.role) >= rank(&actor.role) {
+        return Err(Error::new(ErrorKind::Forbidden, "Ask an administrator", "You can't moderate this user"));
+    }
+    Ok(target)
+}
+
+#[get("/admin/actions")]
+pub async fn list_actions(moderator: Result<Moderator, Error>, client: &State<PrismaClient>) -> Result<Json<Vec<moderation_action::Data>>, Error> {
+    moderator?;
+    let actions = client.moderation_action()
+        .find_many(vec![])
+        .order_by(moderation_action::id::order(Direction::Desc))
+        .take(100)
+        .exec().await
+        .map_err(Error::database)?;
+    Ok(Json(actions))
+}
+
+#[post("/admin/users/<id>/ban", data = "<body>")]
+pub async fn ban_user(moderator: Result<Moderator, Error>, id: i64, body: Json<Reason>, redis: &State<Redis>, client: &State<PrismaClient>) -> Result<Json<user::Data>, Error> {
+    let Moderator(actor) = moderator?;
+    require_reason(&body.reason)?;
+    target_user(client, &actor, id).await?;
+    let reason = body.into_inner().reason;
+    let (user, _) = client._batch((
+        client.user().update(user::id::equals(id), vec![
+            user::banned_at::set(Some(Utc::now().into())),
+            user::ban_reason::set(Some(reason.clone())),
+        ]),
+        client.moderation_action().create(actor.id, "ban".into(), format!("user:{id}"), reason, vec![]),
+    ))
+        .await
+        .map_err(Error::database)?;
+    // Banned users are refused by every guard already; this also drops
+    // their sessions from the list.
+    sessions::revoke_all(redis, id as u64).await
+        .map_err(Error::database)?;
+    Ok(Json(user))
+}
+
+#[post("/admin/users/<id>/unban", data = "<body>")]
+pub async fn unban_user(moderator: Result<Moderator, Error>, id: i64, body: Json<Reason>, client: &State<PrismaClient>) -> Result<Json<user::Data>, Error> {
+    let Moderator(actor) = moderator?;
+    require_reason(&body.reason)?;
+    target_user(client, &actor, id).await?;
+    let (user, _) = client._batch((
+        client.user().update(user::id::equals(id), vec![
+            user::banned_at::set(None),
+            user::ban_reason::set(None),
+        ]),
+        client.moderation_action().create(actor.id, "unban".into(), format!("user:{id}"), body.into_inner().reason, vec![]),
+    ))
+        .await
+        .map_err(Error::database)?;
+    Ok(Json(user))
+}
+
+#[put("/admin/users/<id>/role", data = "<body>")]
+pub async fn set_role(admin: Result<Admin, Error>, id: i64, body: Json<RoleChange>, client: &State<PrismaClient>) -> Result<Json<user::Data>, Error> {
+    let Admin(actor) = admin?;
+    require_reason(&body.reason)?;
+    target_user(client, &actor, id).await?;
+    let RoleChange { role, reason } = body.into_inner();
+    let action = format!("set_role:{role:?}");
+    let (user, _) = client._batch((
+        client.user().update(user::id::equals(id), vec![user::role::set(role)]),
+        client.moderation_action().create(actor.id, action, format!("user:{id}"), reason, vec![]),
+    ))
+        .await
+        .map_err(Error::database)?;
+    Ok(Json(user))
+}
+
+#[get("/admin/names")]
+pub async fn list_locked_names(moderator: Result<Moderator, Error>, client: &State<PrismaClient>) -> Result<Json<Vec<locked_name::Data>>, Error> {
+    moderator?;
+    let names = client.locked_name()
+        .find_many(vec![])
+        .order_by(locked_name::name::order(Direction::Asc))
+        .exec().await
+        .map_err(Error::database)?;
+    Ok(Json(names))
+}
+
+/// Reserves a plugin name so nobody can publish under it.
+#[post("/admin/names/<name>/lock", data = "<body>")]
+pub async fn lock_name(moderator: Result<Moderator, Error>, name: String, body: Json<Reason>, client: &State<PrismaClient>) -> Result<Json<locked_name::Data>, Error> {
+    let Moderator(actor) = moderator?;
+    require_reason(&body.reason)?;
+    let reason = body.into_inner().reason;
+    let (locked, _) = client._batch((
+        client.locked_name().upsert(
+            locked_name::name::equals(name.clone()),
+            locked_name::create(name.clone(), reason.clone(), actor.id, vec![]),
+            vec![
+                locked_name::reason::set(reason.clone()),
+                locked_name::locked_by::set(actor.id),
+            ]
+        ),
+        client.moderation_action().create(actor.id, "lock_name".into(), format!("name:{name}"), reason, vec![]),
+    ))
+        .await
+        .map_err(Error::database)?;
+    Ok(Json(locked))
+}
+
+#[post("/admin/names/<name>/unlock", data = "<body>")]
+pub async fn unlock_name(moderator: Result<Moderator, Error>, name: String, body: Json<Reason>, client: &State<PrismaClient>) -> Result<(), Error> {
+    let Moderator(actor) = moderator?;
+    require_reason(&body.reason)?;
+    let locked = client.locked_name()
+        .find_unique(locked_name::name::equals(name.clone()))
+        .exec().await
+        .map_err(Error::database)?;
+    if locked.is_none() {
+        return Err(Error::new(ErrorKind::NotFound, "List locked names with `GET /api/admin/names`", "This name isn't locked"));
+    }
+    // If it was unlocked meanwhile, the delete fails and nothing is recorded.
+    client._batch((
+        client.locked_name().delete(locked_name::name::equals(name.clone())),
+        client.moderation_action().create(actor.id, "unlock_name".into(), format!("name:{name}"), body.into_inner().reason, vec![]),
+    ))
+        .await
+        .map_err(Error::database)?;
+    Ok(())
+}
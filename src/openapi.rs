@@ -0,0 +1,380 @@
+//! OpenAPI 3 description of the HTTP API, served at `/api/openapi.json` with a
+//! viewer at `/api/docs`.
+//!
+//! The viewer is Swagger UI 5.17.14, vendored in `static/swagger-ui` and
+//! served from the binary, so the docs page loads no code from elsewhere.
+//!
+//! Paths come from the routes mounted under `/api/` at ignition, so a new
+//! route shows up without touching this file. Summaries and schemas are
+//! looked up in [`operations`]; routes missing there are listed with generic
+//! responses until they're described. The schemas are written by hand, and
+//! `tests/openapi.rs` checks responses of a running registry against them.
+use rocket::{Build, Rocket, Route, State};
+use rocket::http::ContentType;
+use rocket::response::content::{RawHtml, RawJson};
+use rocket::serde::json::{json, Value};
+
+/// Documentation of one operation: method, path as mounted, summary, request
+/// body schema and success response schema.
+type Operation = (&'static str, &'static str, &'static str, Option<&'static str>, Option<Value>);
+
+fn schema(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{name}") })
+}
+
+fn list_of(name: &str) -> Value {
+    json!({ "type": "array", "items": schema(name) })
+}
+
+fn operations() -> Vec<Operation> {
+    vec![
+        ("GET", "/api/auth/providers", "Identity providers users can log in with", None, Some(list_of("Provider"))),
+        ("GET", "/api/user", "The logged in user", None, Some(schema("User"))),
+        ("DELETE", "/api/session", "Log out", None, None),
+        ("GET", "/api/users/<name>", "Public profile of a user", None, Some(schema("PublicProfile"))),
+        ("GET", "/api/user/export", "Everything stored about the logged in user", None, Some(schema("PersonalData"))),
+        ("DELETE", "/api/user", "Delete the logged in user's account", Some("DeleteAccount"), None),
+        ("GET", "/api/user/identities", "Identities the logged in user can log in with", None, Some(list_of("Identity"))),
+        ("DELETE", "/api/user/identities/<id>", "Unlink an identity, except the last one", None, None),
+        ("GET", "/api/user/keys", "Signing keys of the logged in user", None, Some(list_of("KeyInfo"))),
+        ("POST", "/api/user/keys", "Register a signing key", Some("NewKey"), Some(schema("KeyInfo"))),
+        ("DELETE", "/api/user/keys/<fingerprint>", "Remove a signing key", None, None),
+        ("GET", "/api/log/key", "Public key signing the transparency log, base64 encoded", None, Some(json!({ "type": "string" }))),
+        ("GET", "/api/log/head", "Signed head of the transparency log", None, Some(schema("TreeHead"))),
+        ("GET", "/api/log/entries/<index>", "A log entry and its inclusion proof, in the current tree or the one of `tree_size`", None, Some(schema("InclusionProof"))),
+        ("GET", "/api/log/consistency", "Proof that the tree of size `first` is a prefix of the tree of size `second`", None, Some(schema("ConsistencyProof"))),
+        ("POST", "/api/reports", "Report a plugin", Some("NewReport"), Some(schema("OwnReport"))),
+        ("GET", "/api/reports/mine", "Reports filed by the logged in user", None, Some(list_of("OwnReport"))),
+        ("GET", "/api/sessions", "Sessions of the logged in user", None, Some(list_of("Session"))),
+        ("DELETE", "/api/sessions/<id>", "Revoke a session", None, None),
+        ("DELETE", "/api/sessions", "Revoke every session, including this one", None, None),
+        ("GET", "/api/admin/actions", "Latest moderation actions (moderators)", None, Some(list_of("ModerationAction"))),
+        ("POST", "/api/admin/users/<id>/ban", "Ban a user (moderators)", Some("Reason"), Some(schema("User"))),
+        ("POST", "/api/admin/users/<id>/unban", "Unban a user (moderators)", Some("Reason"), Some(schema("User"))),
+        ("PUT", "/api/admin/users/<id>/role", "Change the role of a user (admins)", Some("RoleChange"), Some(schema("User"))),
+        ("GET", "/api/admin/names", "Locked plugin names (moderators)", None, None),
+        ("POST", "/api/admin/names/<name>/lock", "Reserve a plugin name (moderators)", Some("Reason"), None),
+        ("POST", "/api/admin/names/<name>/unlock", "Release a plugin name (moderators)", Some("Reason"), None),
+        ("GET", "/api/admin/reports", "Moderation queue (moderators)", None, None),
+        ("PUT", "/api/admin/reports/<id>", "Triage a report (moderators)", Some("Triage"), None),
+        ("GET", "/api/openapi.json", "This document", None, Some(json!({ "type": "object" }))),
+        ("GET", "/api/docs", "Swagger UI browsing this document", None, None),
+        ("GET", "/api/docs/swagger-ui.css", "Stylesheet of the Swagger UI", None, None),
+        ("GET", "/api/docs/swagger-ui-bundle.js", "Script of the Swagger UI", None, None),
+    ]
+}
+
+fn schemas() -> Value {
+    let nullable_string = json!({ "type": "string", "nullable": true });
+    let date_time = json!({ "type": "string", "format": "date-time" });
+    json!({
+        "Error": {
+            "type": "object",
+            "required": ["kind", "action", "message", "request_id"],
+            "properties": {
+                "kind": {
+                    "type": "string",
+                    "enum": ["NotLoggedIn", "Forbidden", "NotFound", "Conflict", "ValidationError", "GithubApiError", "ProviderApiError", "RateLimited", "Unavailable", "DatabaseError", "Internal"]
+                },
+                "action": { "type": "string", "description": "What the client can do about it" },
+                "message": { "type": "string" },
+                "request_id": { "type": "string", "description": "Quote it when reporting a bug" }
+            }
+        },
+        "User": {
+            "type": "object",
+            "required": ["id", "name", "username", "avatar_url", "role", "created_at"],
+            "properties": {
+                "id": { "type": "integer", "format": "int64" },
+                "name": { "type": "string", "description": "Unique name on the registry, taken from the login of the first identity" },
+                "username": { "type": "string", "description": "Display name" },
+                "avatar_url": { "type": "string" },
+                "role": { "type": "string", "enum": ["User", "Moderator", "Admin"] },
+                "banned_at": { "type": "string", "format": "date-time", "nullable": true },
+                "ban_reason": nullable_string,
+                "created_at": date_time
+            }
+        },
+        "Provider": {
+            "type": "object",
+            "properties": {
+                "name": { "type": "string", "enum": ["github", "gitlab", "gitea", "dev"] },
+                "title": { "type": "string" },
+                "login_url": { "type": "string" }
+            }
+        },
+        "Identity": {
+            "type": "object",
+            "required": ["id", "provider", "login", "created_at"],
+            "properties": {
+                "id": { "type": "string", "description": "<provider>:<id on the provider>" },
+                "provider": { "type": "string" },
+                "login": { "type": "string" },
+                "created_at": date_time
+            }
+        },
+        "PublicProfile": {
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer", "format": "int64" },
+                "name": { "type": "string" },
+                "username": { "type": "string" },
+                "avatar_url": { "type": "string" },
+                "joined_at": date_time
+            }
+        },
+        "DeleteAccount": {
+            "type": "object",
+            "required": ["confirm"],
+            "properties": { "confirm": { "type": "string", "description": "The account's name" } }
+        },
+        "NewKey": {
+            "type": "object",
+            "required": ["public_key", "proof"],
+            "properties": {
+                "public_key": { "type": "string", "description": "Base64 encoded ed25519 public key" },
+                "proof": { "type": "string", "description": "Base64 signature of the public key by its private key" }
+            }
+        },
+        "KeyInfo": {
+            "type": "object",
+            "required": ["fingerprint", "public_key", "created_at"],
+            "properties": {
+                "fingerprint": { "type": "string" },
+                "public_key": { "type": "string" },
+                "created_at": date_time
+            }
+        },
+        "TreeHead": {
+            "type": "object",
+            "properties": {
+                "tree_size": { "type": "integer" },
+                "root_hash": { "type": "string" },
+                "timestamp": { "type": "integer", "description": "Milliseconds since the epoch" },
+                "signature": { "type": "string" }
+            }
+        },
+        "InclusionProof": {
+            "type": "object",
+            "properties": {
+                "index": { "type": "integer" },
+                "data": { "type": "string" },
+                "leaf_hash": { "type": "string" },
+                "tree_size": { "type": "integer", "description": "Size of the tree the audit path is for" },
+                "audit_path": { "type": "array", "items": { "type": "string" } },
+                "tree_head": {
+                    "allOf": [schema("TreeHead")],
+                    "description": "The current head, left out when `tree_size` was given"
+                }
+            }
+        },
+        "ConsistencyProof": {
+            "type": "object",
+            "properties": {
+                "first": { "type": "integer" },
+                "second": { "type": "integer" },
+                "proof": { "type": "array", "items": { "type": "string" } }
+            }
+        },
+        "NewReport": {
+            "type": "object",
+            "required": ["plugin", "category", "description"],
+            "properties": {
+                "plugin": { "type": "string" },
+                "version": nullable_string,
+                "category": { "type": "string", "enum": ["Malware", "Spam", "License", "Impersonation"] },
+                "description": { "type": "string", "maxLength": 5000 }
+            }
+        },
+        "OwnReport": {
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer" },
+                "plugin": { "type": "string" },
+                "version": nullable_string,
+                "category": { "type": "string" },
+                "status": { "type": "string", "enum": ["Open", "Triaged", "Resolved", "Dismissed"] },
+                "created_at": date_time,
+                "updated_at": date_time
+            }
+        },
+        "Session": {
+            "type": "object",
+            "required": ["id", "current", "created_at", "last_seen"],
+            "properties": {
+                "id": { "type": "string" },
+                "current": { "type": "boolean" },
+                "created_at": date_time,
+                "last_seen": { "type": "string", "format": "date-time", "description": "Updated at most once a minute" },
+                "user_agent": nullable_string
+            }
+        },
+        "ModerationAction": {
+            "type": "object",
+            "required": ["id", "actor_id", "action", "target", "reason", "created_at"],
+            "properties": {
+                "id": { "type": "integer" },
+                "actor_id": { "type": "integer", "format": "int64" },
+                "action": { "type": "string", "description": "e.g. `ban`, `set_role:Moderator` or `report:Triaged`" },
+                "target": { "type": "string", "description": "`user:<id>`, `name:<name>` or `report:<id>`" },
+                "reason": { "type": "string" },
+                "created_at": date_time
+            }
+        },
+        "PersonalData": {
+            "type": "object",
+            "required": ["profile", "identities", "signing_keys", "reports", "moderation_actions", "moderation_received", "sessions"],
+            "properties": {
+                "profile": schema("User"),
+                "identities": list_of("Identity"),
+                "signing_keys": list_of("KeyInfo"),
+                "reports": list_of("OwnReport"),
+                "moderation_actions": {
+                    "allOf": [list_of("ModerationAction")],
+                    "description": "Actions the user took as a moderator"
+                },
+                "moderation_received": {
+                    "allOf": [list_of("ModerationAction")],
+                    "description": "Actions moderators took on the user"
+                },
+                "sessions": list_of("Session")
+            }
+        },
+        "Reason": {
+            "type": "object",
+            "required": ["reason"],
+            "properties": { "reason": { "type": "string" } }
+        },
+        "RoleChange": {
+            "type": "object",
+            "required": ["role", "reason"],
+            "properties": {
+                "role": { "type": "string", "enum": ["User", "Moderator", "Admin"] },
+                "reason": { "type": "string" }
+            }
+        },
+        "Triage": {
+            "type": "object",
+            "required": ["status", "note"],
+            "properties": {
+                "status": { "type": "string", "enum": ["Open", "Triaged", "Resolved", "Dismissed"] },
+                "note": { "type": "string" }
+            }
+        }
+    })
+}
+
+/// Turns `/users/<login>` into `/users/{login}` and lists its parameters.
+fn path_and_parameters(route: &Route) -> (String, Vec<Value>) {
+    let mut parameters = vec![];
+    let segments: Vec<String> = route.uri.path().split('/')
+        .map(|segment| match segment.strip_prefix('<').and_then(|s| s.strip_suffix('>')) {
+            Some(name) => {
+                let name = name.trim_end_matches("..");
+                parameters.push(json!({ "name": name, "in": "path", "required": true, "schema": { "type": "string" } }));
+                format!("{{{name}}}")
+            }
+            None => segment.to_string()
+        })
+        .collect();
+    for query in route.uri.query().unwrap_or_default().split('&') {
+        if let Some(name) = query.strip_prefix('<').and_then(|s| s.strip_suffix('>')) {
+            parameters.push(json!({ "name": name, "in": "query", "required": false, "schema": { "type": "string" } }));
+        }
+    }
+    (segments.join("/"), parameters)
+}
+
+pub fn document<'a>(routes: impl Iterator<Item = &'a Route>) -> Value {
+    let operations = operations();
+    let mut paths = json!({});
+    for route in routes.filter(|route| route.uri.path().starts_with("/api/")) {
+        let method = route.method.as_str();
+        let (path, parameters) = path_and_parameters(route);
+        let documented = operations.iter().find(|(m, p, ..)| *m == method && *p == route.uri.path());
+        let mut operation = json!({
+            "operationId": route.name.as_deref().unwrap_or_default(),
+            "parameters": parameters,
+            "responses": {
+                "default": {
+                    "description": "Error",
+                    "content": { "application/json": { "schema": schema("Error") } }
+                }
+            }
+        });
+        let success = match documented {
+            Some((_, _, summary, body, response)) => {
+                operation["summary"] = json!(summary);
+                if let Some(body) = body {
+                    operation["requestBody"] = json!({
+                        "required": true,
+                        "content": { "application/json": { "schema": schema(body) } }
+                    });
+                }
+                match response {
+                    Some(response) => json!({ "description": "Success", "content": { "application/json": { "schema": response } } }),
+                    None => json!({ "description": "Success" })
+                }
+            }
+            None => json!({ "description": "Success" })
+        };
+        operation["responses"]["200"] = success;
+        paths[path.as_str()][method.to_lowercase().as_str()] = operation;
+    }
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Lapce Registry",
+            "version": env!("CARGO_PKG_VERSION")
+        },
+        "components": {
+            "schemas": schemas(),
+            "securitySchemes": {
+                "session": { "type": "apiKey", "in": "cookie", "name": crate::SESSION_COOKIE }
+            }
+        },
+        "security": [{ "session": [] }],
+        "paths": paths
+    })
+}
+
+/// The rendered document, built once at ignition.
+pub struct OpenApi(String);
+
+impl OpenApi {
+    pub fn from_rocket(rocket: &Rocket<Build>) -> Self {
+        Self(document(rocket.routes()).to_string())
+    }
+}
+
+#[get("/openapi.json")]
+pub fn openapi(document: &State<OpenApi>) -> RawJson<String> {
+    RawJson(document.0.clone())
+}
+
+#[get("/docs")]
+pub fn docs() -> RawHtml<&'static str> {
+    RawHtml(r##"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="utf-8">
+    <title>Lapce Registry API</title>
+    <link rel="stylesheet" href="/api/docs/swagger-ui.css">
+</head>
+<body>
+    <div id="docs"></div>
+    <script src="/api/docs/swagger-ui-bundle.js"></script>
+    <script>SwaggerUIBundle({ url: "/api/openapi.json", dom_id: "#docs" });</script>
+</body>
+</html>"##)
+}
+
+#[get("/docs/swagger-ui.css")]
+pub fn docs_style() -> (ContentType, &'static str) {
+    (ContentType::CSS, include_str!("../static/swagger-ui/swagger-ui.css"))
+}
+
+#[get("/docs/swagger-ui-bundle.js")]
+pub fn docs_script() -> (ContentType, &'static str) {
+    (ContentType::JavaScript, include_str!("../static/swagger-ui/swagger-ui-bundle.js"))
+}
@@ -0,0 +1,199 @@
+//! Sliding window rate limits stored in Redis.
+//!
+//! Routes opt in with a `RateLimit<Class>` guard. Each request is counted
+//! against the client IP, and also against the logged in user and the bearer
+//! token when there is one; going over any of them fails the request with
+//! 429 and a `Retry-After` header.
+//!
+//! The client IP is the address of the connection, or behind a reverse proxy
+//! the one the proxy added to `CLIENT_IP_HEADER`. Headers sent by the client
+//! itself, like the `X-Real-IP` Rocket trusts, are never used.
+//!
+//! Limits are configured with `RATE_LIMIT_<CLASS>` as `<requests>/<seconds>`,
+//! for example `RATE_LIMIT_LOGIN=10/60` (see [`crate::config`]).
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::net::IpAddr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use rand::Rng;
+use rocket::http::{Header, Status};
+use rocket::outcome::Outcome;
+use rocket::request::{self, FromRequest, Request};
+use sha2::{Digest, Sha256};
+use crate::Session;
+use crate::config::Config;
+use crate::error::*;
+use crate::sessions::Redis;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Limit {
+    pub requests: u32,
+    pub window: Duration
+}
+
+impl Limit {
+    const fn per_minute(requests: u32) -> Self {
+        Self { requests, window: Duration::from_secs(60) }
+    }
+
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        let (requests, seconds) = value.split_once('/')?;
+        Some(Self {
+            requests: requests.trim().parse().ok()?,
+            window: Duration::from_secs(seconds.trim().parse().ok()?)
+        })
+    }
+}
+
+/// A group of routes sharing a limit.
+pub trait RouteClass: Send + Sync + 'static {
+    const NAME: &'static str;
+    const DEFAULT: Limit;
+}
+
+pub struct Login;
+impl RouteClass for Login {
+    const NAME: &'static str = "login";
+    const DEFAULT: Limit = Limit::per_minute(10);
+}
+
+pub struct Publish;
+impl RouteClass for Publish {
+    const NAME: &'static str = "publish";
+    const DEFAULT: Limit = Limit::per_minute(5);
+}
+
+pub struct Search;
+impl RouteClass for Search {
+    const NAME: &'static str = "search";
+    const DEFAULT: Limit = Limit::per_minute(60);
+}
+
+pub struct Download;
+impl RouteClass for Download {
+    const NAME: &'static str = "download";
+    const DEFAULT: Limit = Limit::per_minute(120);
+}
+
+/// Limits overridden in the configuration, by class name, and where to find
+/// the client IP.
+pub struct RateLimits {
+    limits: HashMap<String, Limit>,
+    client_ip_header: Option<String>
+}
+
+impl RateLimits {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            limits: config.rate_limits.clone(),
+            client_ip_header: config.client_ip_header.clone()
+        }
+    }
+
+    fn get<C: RouteClass>(&self) -> Limit {
+        self.limits.get(C::NAME).copied().unwrap_or(C::DEFAULT)
+    }
+
+    /// The last address in the proxy's header, which is the one the proxy
+    /// added; the ones before it come from the client and can be anything.
+    fn client_ip(&self, request: &Request<'_>) -> Option<IpAddr> {
+        match &self.client_ip_header {
+            Some(header) => request.headers().get(header).last()?
+                .rsplit(',').next()?
+                .trim().parse().ok(),
+            None => request.remote().map(|remote| remote.ip())
+        }
+    }
+}
+
+/// Atomically drops hits older than the window, then either records this hit
+/// or returns how many milliseconds to wait for the oldest one to expire.
+const SLIDING_WINDOW: &str = r"
+local key = KEYS[1]
+local now = tonumber(ARGV[1])
+local window = tonumber(ARGV[2])
+local limit = tonumber(ARGV[3])
+redis.call('ZREMRANGEBYSCORE', key, 0, now - window)
+if redis.call('ZCARD', key) >= limit then
+    local oldest = redis.call('ZRANGE', key, 0, 0, 'WITHSCORES')
+    return math.max(1, tonumber(oldest[2]) + window - now)
+end
+redis.call('ZADD', key, now, ARGV[4])
+redis.call('PEXPIRE', key, window)
+return 0
+";
+
+/// Time the client has to wait before retrying, stored for the 429 catcher.
+#[derive(Clone, Copy, Default)]
+struct RetryAfter(u64);
+
+async fn hit(redis: &Redis, key: String, limit: Limit) -> redis::RedisResult<u64> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+    let member = format!("{now}-{}", rand::thread_rng().gen::<u32>());
+    redis::Script::new(SLIDING_WINDOW)
+        .key(key)
+        .arg(now)
+        .arg(limit.window.as_millis() as u64)
+        .arg(limit.requests)
+        .arg(member)
+        .invoke_async(&mut redis.0.clone())
+        .await
+}
+
+/// Passes if the request is within the limits of the route class `C`.
+pub struct RateLimit<C: RouteClass>(PhantomData<C>);
+
+#[rocket::async_trait]
+impl<'r, C: RouteClass> FromRequest<'r> for RateLimit<C> {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let (redis, limits) = match (request.rocket().state::<Redis>(), request.rocket().state::<RateLimits>()) {
+            (Some(redis), Some(limits)) => (redis, limits),
+            _ => return Outcome::Success(RateLimit(PhantomData))
+        };
+        let limit = limits.get::<C>();
+
+        let mut subjects = vec![];
+        if let Some(ip) = limits.client_ip(request) {
+            subjects.push(format!("ip:{ip}"));
+        }
+        if let Outcome::Success(session) = request.guard::<Session<'_>>().await {
+            if let Ok(Some(info)) = session.get().await {
+                subjects.push(format!("user:{}", info.id));
+            }
+        }
+        if let Some(token) = request.headers().get_one("Authorization").and_then(|value| value.strip_prefix("Bearer ")) {
+            subjects.push(format!("token:{}", hex::encode(&Sha256::digest(token.as_bytes())[..16])));
+        }
+
+        let mut wait = 0;
+        for subject in subjects {
+            match hit(redis, format!("ratelimit:{}:{subject}", C::NAME), limit).await {
+                Ok(ms) => wait = wait.max(ms),
+                Err(err) => warn!("Rate limiting unavailable: {}", err)
+            }
+        }
+        if wait > 0 {
+            request.local_cache(|| RetryAfter((wait + 999) / 1000));
+            return Outcome::Failure((Status::TooManyRequests, ()));
+        }
+        Outcome::Success(RateLimit(PhantomData))
+    }
+}
+
+#[derive(Responder)]
+#[response(status = 429)]
+pub struct TooManyRequests {
+    body: Error,
+    retry_after: Header<'static>
+}
+
+#[catch(429)]
+pub fn too_many_requests(request: &Request<'_>) -> TooManyRequests {
+    let RetryAfter(seconds) = *request.local_cache(RetryAfter::default);
+    TooManyRequests {
+        body: Error::new(ErrorKind::RateLimited, format!("Retry in {seconds} seconds"), "Too many requests"),
+        retry_after: Header::new("Retry-After", seconds.to_string())
+    }
+}
@@ -0,0 +1,136 @@
+//! Abuse reports filed by users and the moderation queue to handle them.
+use prisma_client_rust::Direction;
+use rocket::State;
+use rocket::serde::json::Json;
+use rocket::serde::{Deserialize, Serialize};
+use crate::db::prisma::{report, user, PrismaClient, ReportCategory, ReportStatus};
+use crate::error::*;
+use crate::moderation::{self, Member, Moderator};
+
+const MAX_DESCRIPTION_LENGTH: usize = 5000;
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct NewReport {
+    pub plugin: String,
+    pub version: Option<String>,
+    pub category: ReportCategory,
+    pub description: String
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Triage {
+    pub status: ReportStatus,
+    pub note: String
+}
+
+/// What a reporter sees of their own reports. Moderator notes stay internal.
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct OwnReport {
+    pub id: i32,
+    pub plugin: String,
+    pub version: Option<String>,
+    pub category: ReportCategory,
+    pub status: ReportStatus,
+    pub created_at: String,
+    pub updated_at: String
+}
+
+impl From<report::Data> for OwnReport {
+    fn from(report: report::Data) -> Self {
+        Self {
+            id: report.id,
+            plugin: report.plugin,
+            version: report.version,
+            category: report.category,
+            status: report.status,
+            created_at: report.created_at.to_rfc3339(),
+            updated_at: report.updated_at.to_rfc3339()
+        }
+    }
+}
+
+#[post("/reports", data = "<body>")]
+pub async fn create_report(member: Result<Member, Error>, body: Json<NewReport>, client: &State<PrismaClient>) -> Result<Json<OwnReport>, Error> {
+    let Member(reporter) = member?;
+    let NewReport { plugin, version, category, description } = body.into_inner();
+    if plugin.trim().is_empty() {
+        return Err(Error::new(ErrorKind::ValidationError, "Send the name of the plugin in `plugin`", "The plugin is required"));
+    }
+    if description.trim().is_empty() || description.chars().count() > MAX_DESCRIPTION_LENGTH {
+        return Err(Error::new(
+            ErrorKind::ValidationError,
+            "Describe the problem in `description`",
+            "The description must be between 1 and 5000 characters long"
+        ));
+    }
+    let report = client.report()
+        .create(plugin, category, description, vec![
+            report::reporter::connect(user::id::equals(reporter.id)),
+            report::version::set(version),
+        ])
+        .exec().await
+        .map_err(Error::database)?;
+    Ok(Json(report.into()))
+}
+
+#[get("/reports/mine")]
+pub async fn own_reports(member: Result<Member, Error>, client: &State<PrismaClient>) -> Result<Json<Vec<OwnReport>>, Error> {
+    let Member(reporter) = member?;
+    let reports = client.report()
+        .find_many(vec![report::reporter_id::equals(Some(reporter.id))])
+        .order_by(report::created_at::order(Direction::Desc))
+        .exec().await
+        .map_err(Error::database)?;
+    Ok(Json(reports.into_iter().map(OwnReport::from).collect()))
+}
+
+/// The moderation queue, oldest first. Shows open reports unless `status`
+/// is given.
+#[get("/admin/reports?<status>")]
+pub async fn report_queue(moderator: Result<Moderator, Error>, status: Option<&str>, client: &State<PrismaClient>) -> Result<Json<Vec<report::Data>>, Error> {
+    moderator?;
+    let status = match status.unwrap_or("Open") {
+        "Open" => ReportStatus::Open,
+        "Triaged" => ReportStatus::Triaged,
+        "Resolved" => ReportStatus::Resolved,
+        "Dismissed" => ReportStatus::Dismissed,
+        _ => return Err(Error::new(
+            ErrorKind::ValidationError,
+            "Use one of Open, Triaged, Resolved or Dismissed",
+            "Unknown report status"
+        ))
+    };
+    let reports = client.report()
+        .find_many(vec![report::status::equals(status)])
+        .order_by(report::created_at::order(Direction::Asc))
+        .exec().await
+        .map_err(Error::database)?;
+    Ok(Json(reports))
+}
+
+#[put("/admin/reports/<id>", data = "<body>")]
+pub async fn triage_report(moderator: Result<Moderator, Error>, id: i32, body: Json<Triage>, client: &State<PrismaClient>) -> Result<Json<report::Data>, Error> {
+    let Moderator(actor) = moderator?;
+    moderation::require_reason(&body.note)?;
+    let Triage { status, note } = body.into_inner();
+    let action = format!("report:{status:?}");
+    let existing = client.report()
+        .find_unique(report::id::equals(id))
+        .exec().await
+        .map_err(Error::database)?;
+    if existing.is_none() {
+        return Err(Error::new(ErrorKind::NotFound, "Check the report id", "There's no report with this id"));
+    }
+    let (report, _) = client._batch((
+        client.report().update(report::id::equals(id), vec![
+            report::status::set(status),
+            report::moderator_note::set(Some(note.clone())),
+            report::handled_by::set(Some(actor.id)),
+        ]),
+        client.moderation_action().create(actor.id, action, format!("report:{id}"), note, vec![]),
+    )).await.map_err(Error::database)?;
+    Ok(Json(report))
+}
@@ -0,0 +1,214 @@
+//! Tracks every session of a user in Redis so they can be listed and revoked
+//! from another device.
+//!
+//! Each user has a hash at `sessions:<user id>` mapping the `sid` stored in
+//! `SessionInfo` to its metadata. A session whose `sid` isn't in that hash
+//! anymore is dropped by [`SessionTracker`] before it reaches any route.
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use prisma_client_rust::chrono::{TimeZone, Utc};
+use rand::Rng;
+use rand::distributions::Alphanumeric;
+use redis::AsyncCommands;
+use redis::aio::ConnectionManager;
+use rocket::{Data, Request, State};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::outcome::Outcome;
+use rocket::request::{self, FromRequest};
+use rocket::serde::json::{self, Json};
+use rocket::serde::{Deserialize, Serialize};
+use crate::Session;
+use crate::error::*;
+use crate::logging::RequestUser;
+use crate::moderation::Member;
+
+pub const SESSION_DURATION: Duration = Duration::from_secs(3600 * 24 * 3);
+
+/// How often `last_seen` is written back, to avoid a Redis write per request.
+const LAST_SEEN_RESOLUTION: u64 = 60;
+
+/// Shared async Redis connection.
+#[derive(Clone)]
+pub struct Redis(pub ConnectionManager);
+
+/// What Redis stores about a session, with times in seconds since the epoch.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct SessionMeta {
+    pub created_at: u64,
+    pub last_seen: u64,
+    pub user_agent: Option<String>
+}
+
+/// A session as `GET /api/sessions` lists it, with RFC 3339 times like the
+/// rest of the API.
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct SessionListing {
+    pub id: String,
+    pub current: bool,
+    pub created_at: String,
+    pub last_seen: String,
+    pub user_agent: Option<String>
+}
+
+/// The `User-Agent` header, if any.
+pub struct UserAgent(pub Option<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for UserAgent {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        Outcome::Success(UserAgent(request.headers().get_one("User-Agent").map(String::from)))
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+fn rfc3339(secs: u64) -> String {
+    Utc.timestamp_opt(secs as i64, 0).single().map(|time| time.to_rfc3339()).unwrap_or_default()
+}
+
+fn key(user_id: u64) -> String {
+    format!("sessions:{user_id}")
+}
+
+fn redis_error(err: redis::RedisError) -> Error {
+    Error::new(ErrorKind::DatabaseError(err.to_string()), "Try again", "Can't reach the session store")
+}
+
+pub fn new_session_id() -> String {
+    rand::thread_rng().sample_iter(&Alphanumeric).take(32).map(char::from).collect()
+}
+
+/// Records a new session for `user_id`.
+pub async fn register(redis: &Redis, user_id: u64, sid: &str, user_agent: Option<String>) -> redis::RedisResult<()> {
+    let meta = SessionMeta { created_at: now(), last_seen: now(), user_agent };
+    let mut con = redis.0.clone();
+    let _: () = con.hset(key(user_id), sid, json::to_string(&meta).unwrap_or_default()).await?;
+    con.expire(key(user_id), SESSION_DURATION.as_secs() as usize).await
+}
+
+/// Live sessions of a user. Entries older than the session duration are
+/// pruned on the way.
+pub async fn list(redis: &Redis, user_id: u64) -> redis::RedisResult<HashMap<String, SessionMeta>> {
+    let mut con = redis.0.clone();
+    let entries: HashMap<String, String> = con.hgetall(key(user_id)).await?;
+    let mut sessions = HashMap::new();
+    for (sid, meta) in entries {
+        match json::from_str::<SessionMeta>(&meta) {
+            Ok(meta) if meta.created_at + SESSION_DURATION.as_secs() > now() => {
+                sessions.insert(sid, meta);
+            }
+            _ => con.hdel(key(user_id), &sid).await?
+        }
+    }
+    Ok(sessions)
+}
+
+/// Revokes one session. Returns whether it existed.
+pub async fn revoke(redis: &Redis, user_id: u64, sid: &str) -> redis::RedisResult<bool> {
+    let mut con = redis.0.clone();
+    let removed: u32 = con.hdel(key(user_id), sid).await?;
+    Ok(removed > 0)
+}
+
+/// Revokes every session of a user.
+pub async fn revoke_all(redis: &Redis, user_id: u64) -> redis::RedisResult<()> {
+    let mut con = redis.0.clone();
+    con.del(key(user_id)).await
+}
+
+/// Drops sessions that were revoked and keeps `last_seen` up to date. Also
+/// notes the user of the request for the log line.
+pub struct SessionTracker;
+
+#[rocket::async_trait]
+impl Fairing for SessionTracker {
+    fn info(&self) -> Info {
+        Info {
+            name: "Session tracker",
+            kind: Kind::Request
+        }
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        let redis = match request.rocket().state::<Redis>() {
+            Some(redis) => redis.clone(),
+            None => return
+        };
+        let session = match request.guard::<Session<'_>>().await {
+            Outcome::Success(session) => session,
+            _ => return
+        };
+        let info = match session.get().await {
+            Ok(Some(info)) => info,
+            _ => return
+        };
+        let mut con = redis.0.clone();
+        let meta: Option<String> = match con.hget(key(info.id), &info.sid).await {
+            Ok(meta) => meta,
+            // Keep serving if Redis hiccups, the session store would fail too.
+            Err(_) => return
+        };
+        match meta.and_then(|meta| json::from_str::<SessionMeta>(&meta).ok()) {
+            Some(mut meta) => {
+                request.local_cache(|| RequestUser(Some(info.id)));
+                if now().saturating_sub(meta.last_seen) >= LAST_SEEN_RESOLUTION {
+                    meta.last_seen = now();
+                    let _: redis::RedisResult<()> = con.hset(key(info.id), &info.sid, json::to_string(&meta).unwrap_or_default()).await;
+                }
+            }
+            None => {
+                session.remove().await.ok();
+            }
+        }
+    }
+}
+
+#[get("/sessions")]
+pub async fn list_sessions(member: Result<Member, Error>, session: Session<'_>, redis: &State<Redis>) -> Result<Json<Vec<SessionListing>>, Error> {
+    let Member(user) = member?;
+    Ok(Json(listings(redis, user.id as u64, &session).await?))
+}
+
+/// The sessions of `user_id`, most recently seen first, marking the one of
+/// `session`.
+pub async fn listings(redis: &Redis, user_id: u64, session: &Session<'_>) -> Result<Vec<SessionListing>, Error> {
+    let current = session.get().await.ok().flatten().map(|info| info.sid);
+    let mut sessions: Vec<(String, SessionMeta)> = list(redis, user_id).await
+        .map_err(redis_error)?
+        .into_iter()
+        .collect();
+    sessions.sort_by(|(_, a), (_, b)| b.last_seen.cmp(&a.last_seen));
+    Ok(sessions.into_iter()
+        .map(|(id, meta)| SessionListing {
+            current: Some(&id) == current.as_ref(),
+            id,
+            created_at: rfc3339(meta.created_at),
+            last_seen: rfc3339(meta.last_seen),
+            user_agent: meta.user_agent
+        })
+        .collect())
+}
+
+#[delete("/sessions/<id>")]
+pub async fn revoke_session(member: Result<Member, Error>, id: &str, redis: &State<Redis>) -> Result<(), Error> {
+    let Member(user) = member?;
+    if !revoke(redis, user.id as u64, id).await.map_err(redis_error)? {
+        return Err(Error::new(ErrorKind::NotFound, "List your sessions with `GET /api/sessions`", "There's no session with this id"));
+    }
+    Ok(())
+}
+
+/// Logs out everywhere, including this session.
+#[delete("/sessions")]
+pub async fn revoke_all_sessions(member: Result<Member, Error>, session: Session<'_>, redis: &State<Redis>) -> Result<(), Error> {
+    let Member(user) = member?;
+    revoke_all(redis, user.id as u64).await.map_err(redis_error)?;
+    session.remove().await.ok();
+    Ok(())
+}
@@ -0,0 +1,181 @@
+use ed25519_dalek::{PublicKey, Signature, PUBLIC_KEY_LENGTH};
+use rocket::State;
+use rocket::serde::json::Json;
+use rocket::serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use crate::Session;
+use crate::db::prisma::{signing_key, user, PrismaClient};
+use crate::error::*;
+use crate::transparency::{self, Event};
+
+/// Request body of `POST /api/user/keys`.
+///
+/// `proof` is a signature of the raw public key made with its own private key,
+/// so nobody can register a key they don't hold.
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct NewKey {
+    pub public_key: String,
+    pub proof: String
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct KeyInfo {
+    pub fingerprint: String,
+    pub public_key: String,
+    pub created_at: String
+}
+
+impl From<signing_key::Data> for KeyInfo {
+    fn from(key: signing_key::Data) -> Self {
+        Self {
+            fingerprint: key.fingerprint,
+            public_key: base64::encode(&key.public_key),
+            created_at: key.created_at.to_rfc3339()
+        }
+    }
+}
+
+/// Hex encoded SHA-256 of the raw 32 byte public key.
+pub fn fingerprint(public_key: &[u8]) -> String {
+    hex::encode(Sha256::digest(public_key))
+}
+
+fn invalid(message: &str) -> Error {
+    Error::new(ErrorKind::ValidationError, "Send a base64 encoded ed25519 public key and signature", message)
+}
+
+/// Checks a detached ed25519 signature made over an artifact digest.
+pub fn verify_signature(public_key: &[u8], digest: &[u8], signature: &[u8]) -> Result<(), Error> {
+    let invalid_signature = |message: &str| Error::new(
+        ErrorKind::ValidationError,
+        "Sign the artifact digest with a key registered on your account",
+        message
+    );
+    let public_key = PublicKey::from_bytes(public_key)
+        .map_err(|_| invalid_signature("Invalid public key"))?;
+    let signature = Signature::try_from(signature)
+        .map_err(|_| invalid_signature("Malformed signature"))?;
+    public_key.verify_strict(digest, &signature)
+        .map_err(|_| invalid_signature("The signature doesn't match the digest"))
+}
+
+#[get("/user/keys")]
+pub async fn list_keys(session: Session<'_>, client: &State<PrismaClient>) -> Result<Json<Vec<KeyInfo>>, Error> {
+    let session = session.get().await.ok().flatten().ok_or_else(Error::not_logged_in)?;
+    let keys = client.signing_key()
+        .find_many(vec![signing_key::user_id::equals(session.id as i64)])
+        .exec().await
+        .map_err(Error::database)?;
+    Ok(Json(keys.into_iter().map(KeyInfo::from).collect()))
+}
+
+#[post("/user/keys", data = "<key>")]
+pub async fn add_key(session: Session<'_>, key: Json<NewKey>, client: &State<PrismaClient>) -> Result<Json<KeyInfo>, Error> {
+    let session = session.get().await.ok().flatten().ok_or_else(Error::not_logged_in)?;
+    let public_key = base64::decode(&key.public_key)
+        .map_err(|_| invalid("The public key isn't valid base64"))?;
+    if public_key.len() != PUBLIC_KEY_LENGTH {
+        return Err(invalid("An ed25519 public key is 32 bytes long"));
+    }
+    let proof = base64::decode(&key.proof)
+        .map_err(|_| invalid("The proof isn't valid base64"))?;
+    verify_signature(&public_key, &public_key, &proof)
+        .map_err(|_| invalid("The proof must be the public key signed with its private key"))?;
+
+    let fingerprint = fingerprint(&public_key);
+    let existing = client.signing_key()
+        .find_unique(signing_key::fingerprint::equals(fingerprint.clone()))
+        .exec().await
+        .map_err(Error::database)?;
+    if existing.is_some() {
+        return Err(Error::new(ErrorKind::Conflict, "Generate a new key pair", "This key is already registered"));
+    }
+    let user_id = session.id as i64;
+    let event = Event::KeyAdded { user_id, fingerprint: fingerprint.clone() };
+    let mut last_err = None;
+    for _ in 0..transparency::APPEND_ATTEMPTS {
+        let entry = transparency::next_entry(client, &event).await?;
+        let batch = client._batch((
+            client.signing_key().create(fingerprint.clone(), public_key.clone(), user::id::equals(user_id), vec![]),
+            client.log_entry().create(entry.index, entry.kind, entry.data, entry.leaf_hash, vec![]),
+        )).await;
+        match batch {
+            Ok((key, _)) => return Ok(Json(key.into())),
+            Err(err) => last_err = Some(err)
+        }
+    }
+    Err(Error::database(last_err.map(|err| err.to_string()).unwrap_or_default()))
+}
+
+/// Removes the key `fingerprint` of `user_id` and logs its removal, both or
+/// neither.
+pub async fn remove(client: &PrismaClient, user_id: i64, fingerprint: String) -> Result<(), Error> {
+    let event = Event::KeyRemoved { user_id, fingerprint: fingerprint.clone() };
+    let mut last_err = None;
+    for _ in 0..transparency::APPEND_ATTEMPTS {
+        let entry = transparency::next_entry(client, &event).await?;
+        let batch = client._batch((
+            client.signing_key().delete(signing_key::fingerprint::equals(fingerprint.clone())),
+            client.log_entry().create(entry.index, entry.kind, entry.data, entry.leaf_hash, vec![]),
+        )).await;
+        match batch {
+            Ok(_) => return Ok(()),
+            Err(err) => last_err = Some(err)
+        }
+    }
+    Err(Error::database(last_err.map(|err| err.to_string()).unwrap_or_default()))
+}
+
+#[delete("/user/keys/<fingerprint>")]
+pub async fn remove_key(session: Session<'_>, fingerprint: String, client: &State<PrismaClient>) -> Result<(), Error> {
+    let session = session.get().await.ok().flatten().ok_or_else(Error::not_logged_in)?;
+    let key = client.signing_key()
+        .find_first(vec![
+            signing_key::fingerprint::equals(fingerprint.clone()),
+            signing_key::user_id::equals(session.id as i64),
+        ])
+        .exec().await
+        .map_err(Error::database)?;
+    if key.is_none() {
+        return Err(Error::new(ErrorKind::NotFound, "List your keys with `GET /api/user/keys`", "You don't have a key with this fingerprint"));
+    }
+    remove(client, session.id as i64, fingerprint).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test 1 of RFC 8032: a signature of the empty message.
+    const PUBLIC_KEY: &str = "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a";
+    const SIGNATURE: &str = "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b";
+    /// Public key of test 2 of RFC 8032.
+    const OTHER_PUBLIC_KEY: &str = "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c";
+
+    fn bytes(hex: &str) -> Vec<u8> {
+        hex::decode(hex).unwrap()
+    }
+
+    #[test]
+    fn a_valid_signature_is_accepted() {
+        assert!(verify_signature(&bytes(PUBLIC_KEY), b"", &bytes(SIGNATURE)).is_ok());
+    }
+
+    #[test]
+    fn a_signature_of_another_key_or_digest_is_refused() {
+        let err = verify_signature(&bytes(OTHER_PUBLIC_KEY), b"", &bytes(SIGNATURE)).unwrap_err();
+        assert_eq!(err.message, "The signature doesn't match the digest");
+        let err = verify_signature(&bytes(PUBLIC_KEY), b"digest", &bytes(SIGNATURE)).unwrap_err();
+        assert_eq!(err.message, "The signature doesn't match the digest");
+    }
+
+    #[test]
+    fn a_malformed_signature_or_key_is_refused() {
+        let err = verify_signature(&bytes(PUBLIC_KEY), b"", &bytes(SIGNATURE)[..63]).unwrap_err();
+        assert_eq!(err.message, "Malformed signature");
+        let err = verify_signature(&bytes(PUBLIC_KEY)[..31], b"", &bytes(SIGNATURE)).unwrap_err();
+        assert_eq!(err.message, "Invalid public key");
+    }
+}
@@ -0,0 +1,711 @@
+//! Append-only transparency log.
+//!
+//! Every change that affects which artifacts a client should trust is appended
+//! to a Merkle tree following RFC 6962. The registry serves signed tree heads,
+//! inclusion proofs and consistency proofs between tree sizes, so clients
+//! checking that each head extends the last one they saw, and auditors
+//! comparing heads, can tell if two users were ever shown a different history.
+use std::time::{SystemTime, UNIX_EPOCH};
+use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signature, Signer};
+use prisma_client_rust::Direction;
+use rocket::State;
+use rocket::tokio::sync::{Mutex, MutexGuard};
+use rocket::serde::json::{self, Json};
+use rocket::serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use crate::config::Config;
+use crate::db::prisma::{log_entry, PrismaClient};
+use crate::error::*;
+
+pub type Hash = [u8; 32];
+
+/// Events recorded in the log. Publishes, yanks and ownership changes get
+/// their own variants once those routes exist.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "rocket::serde", tag = "kind", rename_all = "snake_case")]
+pub enum Event {
+    KeyAdded { user_id: i64, fingerprint: String },
+    KeyRemoved { user_id: i64, fingerprint: String }
+}
+
+impl Event {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Event::KeyAdded { .. } => "key_added",
+            Event::KeyRemoved { .. } => "key_removed"
+        }
+    }
+}
+
+/// The exact document hashed into a leaf, stored verbatim in `LogEntry.data`.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+struct LeafData {
+    index: i64,
+    timestamp: u64,
+    #[serde(flatten)]
+    event: Event
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct TreeHead {
+    pub tree_size: u64,
+    pub root_hash: String,
+    pub timestamp: u64,
+    pub signature: String
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct InclusionProof {
+    pub index: i64,
+    pub data: String,
+    pub leaf_hash: String,
+    /// Size of the tree the audit path leads to the root of.
+    pub tree_size: u64,
+    pub audit_path: Vec<String>,
+    /// The current head, when the proof is for the current tree.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tree_head: Option<TreeHead>
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ConsistencyProof {
+    pub first: u64,
+    pub second: u64,
+    pub proof: Vec<String>
+}
+
+/// Key used to sign tree heads, configured with `LOG_SIGNING_KEY` (a base64
+/// encoded ed25519 secret key). Tree heads can't be served without it.
+pub struct LogSigner(pub Option<Keypair>);
+
+impl LogSigner {
+    pub fn from_config(config: &Config) -> Self {
+        let keypair = config.log_signing_key.as_deref()
+            .and_then(|key| SecretKey::from_bytes(key).ok())
+            .map(|secret| Keypair { public: PublicKey::from(&secret), secret });
+        Self(keypair)
+    }
+}
+
+pub fn leaf_hash(data: &[u8]) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([0u8]);
+    hasher.update(data);
+    hasher.finalize().into()
+}
+
+fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([1u8]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Largest power of two strictly smaller than `n`.
+fn split_point(n: usize) -> usize {
+    let mut k = 1;
+    while k << 1 < n {
+        k <<= 1;
+    }
+    k
+}
+
+/// Leaf hashes of the log along with the root of every complete subtree, so
+/// roots and proofs over the first `size` leaves cost O(log² n) hashes
+/// instead of rehashing the whole log.
+#[derive(Default)]
+pub struct MerkleTree {
+    /// `levels[h][i]` is the root of the leaves `i * 2^h .. (i + 1) * 2^h`.
+    levels: Vec<Vec<Hash>>
+}
+
+impl MerkleTree {
+    pub fn new(leaves: &[Hash]) -> Self {
+        let mut tree = Self::default();
+        for leaf in leaves {
+            tree.push(*leaf);
+        }
+        tree
+    }
+
+    pub fn len(&self) -> usize {
+        self.levels.first().map_or(0, Vec::len)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn push(&mut self, leaf: Hash) {
+        let mut node = leaf;
+        for height in 0.. {
+            if self.levels.len() == height {
+                self.levels.push(vec![]);
+            }
+            let level = &mut self.levels[height];
+            level.push(node);
+            if level.len() % 2 == 1 {
+                break;
+            }
+            node = node_hash(&level[level.len() - 2], &level[level.len() - 1]);
+        }
+    }
+
+    /// Merkle tree hash of the leaves `start..end`.
+    fn subtree(&self, start: usize, end: usize) -> Hash {
+        let n = end - start;
+        if n == 0 {
+            return Sha256::digest(b"").into();
+        }
+        if n.is_power_of_two() && start.is_multiple_of(n) {
+            return self.levels[n.trailing_zeros() as usize][start / n];
+        }
+        let k = split_point(n);
+        node_hash(&self.subtree(start, start + k), &self.subtree(start + k, end))
+    }
+
+    /// Root of the tree made of the first `size` leaves.
+    pub fn root(&self, size: usize) -> Hash {
+        self.subtree(0, size)
+    }
+
+    /// Audit path for the leaf at `index` in the tree made of the first
+    /// `size` leaves, from the leaf up to the root.
+    pub fn audit_path(&self, index: usize, size: usize) -> Vec<Hash> {
+        let mut path = vec![];
+        let (mut start, mut end) = (0, size);
+        while end - start > 1 {
+            let k = split_point(end - start);
+            if index < start + k {
+                path.push(self.subtree(start + k, end));
+                end = start + k;
+            } else {
+                path.push(self.subtree(start, start + k));
+                start += k;
+            }
+        }
+        path.reverse();
+        path
+    }
+
+    /// Proof that the tree of the first `first` leaves is a prefix of the
+    /// tree of the first `second` (RFC 6962, section 2.1.2), for
+    /// `0 < first <= second`.
+    pub fn consistency_proof(&self, first: usize, second: usize) -> Vec<Hash> {
+        let mut proof = vec![];
+        let (mut m, mut start, mut end) = (first, 0, second);
+        let mut complete = true;
+        while m != end - start {
+            let k = split_point(end - start);
+            if m <= k {
+                proof.push(self.subtree(start + k, end));
+                end = start + k;
+            } else {
+                proof.push(self.subtree(start, start + k));
+                start += k;
+                m -= k;
+                complete = false;
+            }
+        }
+        if !complete {
+            proof.push(self.subtree(start, end));
+        }
+        proof.reverse();
+        proof
+    }
+}
+
+/// Checks that `leaf` is the `index`th leaf of the tree of size `tree_size`
+/// whose root is `root` (RFC 9162, section 2.1.3.2).
+pub fn verify_inclusion(index: u64, tree_size: u64, leaf: &Hash, path: &[Hash], root: &Hash) -> bool {
+    if index >= tree_size {
+        return false;
+    }
+    let (mut fnode, mut snode) = (index, tree_size - 1);
+    let mut hash = *leaf;
+    for sibling in path {
+        if snode == 0 {
+            return false;
+        }
+        if fnode & 1 == 1 || fnode == snode {
+            hash = node_hash(sibling, &hash);
+            while fnode & 1 == 0 && fnode != 0 {
+                fnode >>= 1;
+                snode >>= 1;
+            }
+        } else {
+            hash = node_hash(&hash, sibling);
+        }
+        fnode >>= 1;
+        snode >>= 1;
+    }
+    snode == 0 && &hash == root
+}
+
+/// Checks that the tree of size `first` with root `first_root` is a prefix of
+/// the tree of size `second` with root `second_root` (RFC 9162, section
+/// 2.1.4.2). A client holding an older tree head checks a newer one with it
+/// before trusting it, which is how a log showing different histories to
+/// different users gets caught.
+pub fn verify_consistency(first: u64, second: u64, first_root: &Hash, second_root: &Hash, proof: &[Hash]) -> bool {
+    if first == 0 || first > second {
+        return false;
+    }
+    if first == second {
+        return proof.is_empty() && first_root == second_root;
+    }
+    let mut nodes = proof.to_vec();
+    if first.is_power_of_two() {
+        nodes.insert(0, *first_root);
+    }
+    let (mut fnode, mut snode) = (first - 1, second - 1);
+    while fnode & 1 == 1 {
+        fnode >>= 1;
+        snode >>= 1;
+    }
+    let (mut first_hash, mut second_hash) = match nodes.first() {
+        Some(node) => (*node, *node),
+        None => return false
+    };
+    for node in &nodes[1..] {
+        if snode == 0 {
+            return false;
+        }
+        if fnode & 1 == 1 || fnode == snode {
+            first_hash = node_hash(node, &first_hash);
+            second_hash = node_hash(node, &second_hash);
+            while fnode & 1 == 0 && fnode != 0 {
+                fnode >>= 1;
+                snode >>= 1;
+            }
+        } else {
+            second_hash = node_hash(&second_hash, node);
+        }
+        fnode >>= 1;
+        snode >>= 1;
+    }
+    snode == 0 && &first_hash == first_root && &second_hash == second_root
+}
+
+/// Bytes covered by a tree head signature.
+fn tree_head_message(tree_size: u64, root: &Hash, timestamp: u64) -> Vec<u8> {
+    let mut message = Vec::with_capacity(8 + 32 + 8);
+    message.extend_from_slice(&tree_size.to_be_bytes());
+    message.extend_from_slice(root);
+    message.extend_from_slice(&timestamp.to_be_bytes());
+    message
+}
+
+/// Checks a tree head against the log's public key.
+pub fn verify_tree_head(head: &TreeHead, public_key: &PublicKey) -> bool {
+    let root = match hex::decode(&head.root_hash).ok().and_then(|root| Hash::try_from(root).ok()) {
+        Some(root) => root,
+        None => return false
+    };
+    let signature = match base64::decode(&head.signature).ok()
+        .and_then(|signature| Signature::try_from(signature.as_slice()).ok()) {
+        Some(signature) => signature,
+        None => return false
+    };
+    public_key.verify_strict(&tree_head_message(head.tree_size, &root, head.timestamp), &signature).is_ok()
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64
+}
+
+/// How many times an append is tried when concurrent appends take its index.
+pub const APPEND_ATTEMPTS: usize = 5;
+
+/// A log entry about to be created, see [`next_entry`].
+pub struct NewEntry {
+    pub index: i64,
+    pub kind: String,
+    pub data: String,
+    pub leaf_hash: Vec<u8>
+}
+
+/// The entry `event` gets if it's appended now. Indices are dense, so the
+/// entry can only be created if no other append took its index meanwhile.
+/// Creating it in the same `_batch` as the change it records makes the two
+/// atomic, and a taken index fails the whole batch, which is then retried
+/// with a new entry.
+pub async fn next_entry(client: &PrismaClient, event: &Event) -> Result<NewEntry, Error> {
+    let index = client.log_entry().count(vec![]).exec().await.map_err(Error::database)?;
+    let data = json::to_string(&LeafData { index, timestamp: now(), event: event.clone() })
+        .map_err(Error::database)?;
+    Ok(NewEntry {
+        index,
+        kind: event.kind().to_string(),
+        leaf_hash: leaf_hash(data.as_bytes()).to_vec(),
+        data
+    })
+}
+
+/// The log as last read from the database, with the head signed for it.
+/// The log routes share it, so each request only reads the entries appended
+/// since the previous one and the head is only signed again when the tree
+/// grows.
+#[derive(Default)]
+pub struct LogCache(Mutex<CachedLog>);
+
+#[derive(Default)]
+pub struct CachedLog {
+    pub tree: MerkleTree,
+    head: Option<TreeHead>
+}
+
+impl LogCache {
+    /// Reads the new entries. An entry missing or with a malformed hash
+    /// would shift every later leaf, so it fails the whole tree instead.
+    pub async fn sync(&self, client: &PrismaClient) -> Result<MutexGuard<'_, CachedLog>, Error> {
+        let mut log = self.0.lock().await;
+        let entries = client.log_entry()
+            .find_many(vec![log_entry::index::gte(log.tree.len() as i64)])
+            .order_by(log_entry::index::order(Direction::Asc))
+            .exec().await
+            .map_err(Error::database)?;
+        for entry in entries {
+            let position = log.tree.len();
+            match Hash::try_from(entry.leaf_hash) {
+                Ok(hash) if entry.index == position as i64 => log.tree.push(hash),
+                _ => return Err(Error::database(format!("log entry {} is missing or corrupt", position)))
+            }
+        }
+        Ok(log)
+    }
+}
+
+impl CachedLog {
+    /// Head of the whole tree, signed again only if the tree grew.
+    pub fn head(&mut self, signer: &LogSigner) -> Result<TreeHead, Error> {
+        match &self.head {
+            Some(head) if head.tree_size == self.tree.len() as u64 => Ok(head.clone()),
+            _ => {
+                let head = sign_head(signer, &self.tree)?;
+                self.head = Some(head.clone());
+                Ok(head)
+            }
+        }
+    }
+}
+
+fn sign_head(signer: &LogSigner, tree: &MerkleTree) -> Result<TreeHead, Error> {
+    let keypair = signer.0.as_ref().ok_or_else(|| Error::new(
+        ErrorKind::Unavailable,
+        "If you're the admin, set LOG_SIGNING_KEY",
+        "The transparency log has no signing key"
+    ))?;
+    let root = tree.root(tree.len());
+    let timestamp = now();
+    let signature = keypair.sign(&tree_head_message(tree.len() as u64, &root, timestamp));
+    let head = TreeHead {
+        tree_size: tree.len() as u64,
+        root_hash: hex::encode(root),
+        timestamp,
+        signature: base64::encode(signature.to_bytes())
+    };
+    debug_assert!(verify_tree_head(&head, &keypair.public));
+    Ok(head)
+}
+
+#[get("/log/key")]
+pub fn log_key(signer: &State<LogSigner>) -> Option<String> {
+    signer.0.as_ref().map(|keypair| base64::encode(keypair.public.as_bytes()))
+}
+
+#[get("/log/head")]
+pub async fn tree_head(signer: &State<LogSigner>, cache: &State<LogCache>, client: &State<PrismaClient>) -> Result<Json<TreeHead>, Error> {
+    let mut log = cache.sync(client).await?;
+    Ok(Json(log.head(signer)?))
+}
+
+/// Proves the entry is in the tree of `tree_size`, a head the client already
+/// holds, or else in the current tree, whose signed head is then included.
+#[get("/log/entries/<index>?<tree_size>")]
+pub async fn inclusion_proof(index: i64, tree_size: Option<u64>, signer: &State<LogSigner>, cache: &State<LogCache>, client: &State<PrismaClient>) -> Result<Json<InclusionProof>, Error> {
+    let entry = client.log_entry()
+        .find_unique(log_entry::index::equals(index))
+        .exec().await
+        .map_err(Error::database)?
+        .ok_or_else(|| Error::new(ErrorKind::NotFound, "Use an index lower than the tree size", "There's no log entry with this index"))?;
+    let mut log = cache.sync(client).await?;
+    let (size, tree_head) = match tree_size {
+        Some(size) if size > log.tree.len() as u64 => return Err(Error::new(
+            ErrorKind::NotFound,
+            "Use the size of a tree head the log signed",
+            "The log isn't that large"
+        )),
+        Some(size) if size <= index as u64 => return Err(Error::new(
+            ErrorKind::ValidationError,
+            "Use a tree size larger than the index",
+            "The entry isn't in a tree that small"
+        )),
+        Some(size) => (size as usize, None),
+        None => (log.tree.len(), Some(log.head(signer)?))
+    };
+    let path = log.tree.audit_path(index as usize, size);
+    debug_assert!(verify_inclusion(index as u64, size as u64, &log.tree.levels[0][index as usize], &path, &log.tree.root(size)));
+    Ok(Json(InclusionProof {
+        index,
+        tree_size: size as u64,
+        leaf_hash: hex::encode(&entry.leaf_hash),
+        data: entry.data,
+        audit_path: path.iter().map(hex::encode).collect(),
+        tree_head
+    }))
+}
+
+/// Proves that the tree of size `first` is a prefix of the tree of size
+/// `second`, both at most the current tree size.
+#[get("/log/consistency?<first>&<second>")]
+pub async fn consistency_proof(first: u64, second: u64, cache: &State<LogCache>, client: &State<PrismaClient>) -> Result<Json<ConsistencyProof>, Error> {
+    let log = cache.sync(client).await?;
+    if first == 0 || first > second {
+        return Err(Error::new(ErrorKind::ValidationError, "Ask for 0 < first <= second", "Invalid tree sizes"));
+    }
+    if second > log.tree.len() as u64 {
+        return Err(Error::new(ErrorKind::NotFound, "Use the size of a tree head the log signed", "The log isn't that large"));
+    }
+    let proof = log.tree.consistency_proof(first as usize, second as usize);
+    debug_assert!(verify_consistency(first, second, &log.tree.root(first as usize), &log.tree.root(second as usize), &proof));
+    Ok(Json(ConsistencyProof {
+        first,
+        second,
+        proof: proof.iter().map(hex::encode).collect()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Leaf inputs of the RFC 6962 test vectors used by Certificate
+    /// Transparency implementations.
+    const INPUTS: [&str; 8] = ["", "00", "10", "2021", "3031", "40414243", "5051525354555657", "606162636465666768696a6b6c6d6e6f"];
+
+    /// Roots of the trees made of the first 1 to 8 inputs.
+    const ROOTS: [&str; 8] = [
+        "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d",
+        "fac54203e7cc696cf0dfcb42c92a1d9dbaf70ad9e621f4bd8d98662f00e3c125",
+        "aeb6bcfe274b70a14fb067a5e5578264db0fa9b51af5e0ba159158f329e06e77",
+        "d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7",
+        "4e3bbb1f7b478dcfe71fb631631519a3bca12c9aefca1612bfce4c13a86264d4",
+        "76e67dadbcdf1e10e1b74ddc608abd2f98dfb16fbce75277b5232a127f2087ef",
+        "ddb89be403809e325750d3d263cd78929c2942b7942a34b77e122c9594a74c8c",
+        "5dc9da79a70659a9ad559cb701ded9a2ab9d823aad2f4960cfe370eff4604328"
+    ];
+
+    /// Audit paths from the same test vectors, as (index, tree size, path).
+    const PATHS: [(usize, usize, &[&str]); 4] = [
+        (0, 8, &[
+            "96a296d224f285c67bee93c30f8a309157f0daa35dc5b87e410b78630a09cfc7",
+            "5f083f0a1a33ca076a95279832580db3e0ef4584bdff1f54c8a360f50de3031e",
+            "6b47aaf29ee3c2af9af889bc1fb9254dabd31177f16232dd6aab035ca39bf6e4"
+        ]),
+        (5, 8, &[
+            "bc1a0643b12e4d2d7c77918f44e0f4f79a838b6cf9ec5b5c283e1f4d88599e6b",
+            "ca854ea128ed050b41b35ffc1b87b8eb2bde461e9e3b5596ece6b9d5975a0ae0",
+            "d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7"
+        ]),
+        (2, 3, &["fac54203e7cc696cf0dfcb42c92a1d9dbaf70ad9e621f4bd8d98662f00e3c125"]),
+        (1, 5, &[
+            "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d",
+            "5f083f0a1a33ca076a95279832580db3e0ef4584bdff1f54c8a360f50de3031e",
+            "bc1a0643b12e4d2d7c77918f44e0f4f79a838b6cf9ec5b5c283e1f4d88599e6b"
+        ])
+    ];
+
+    /// Consistency proofs from the same test vectors, as (first, second,
+    /// proof).
+    const CONSISTENCY: [(usize, usize, &[&str]); 4] = [
+        (1, 1, &[]),
+        (1, 8, &[
+            "96a296d224f285c67bee93c30f8a309157f0daa35dc5b87e410b78630a09cfc7",
+            "5f083f0a1a33ca076a95279832580db3e0ef4584bdff1f54c8a360f50de3031e",
+            "6b47aaf29ee3c2af9af889bc1fb9254dabd31177f16232dd6aab035ca39bf6e4"
+        ]),
+        (6, 8, &[
+            "0ebc5d3437fbe2db158b9f126a1d118e308181031d0a949f8dededebc558ef6a",
+            "ca854ea128ed050b41b35ffc1b87b8eb2bde461e9e3b5596ece6b9d5975a0ae0",
+            "d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7"
+        ]),
+        (2, 5, &[
+            "5f083f0a1a33ca076a95279832580db3e0ef4584bdff1f54c8a360f50de3031e",
+            "bc1a0643b12e4d2d7c77918f44e0f4f79a838b6cf9ec5b5c283e1f4d88599e6b"
+        ])
+    ];
+
+    fn leaves(n: usize) -> Vec<Hash> {
+        (0..n).map(|i| leaf_hash(&hex::decode(INPUTS[i % INPUTS.len()]).unwrap()))
+            .collect()
+    }
+
+    fn hash(hex: &str) -> Hash {
+        Hash::try_from(hex::decode(hex).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn split_point_is_the_largest_smaller_power_of_two() {
+        for (n, k) in [(2, 1), (3, 2), (4, 2), (5, 4), (8, 4), (9, 8), (1000, 512), (1025, 1024)] {
+            assert_eq!(split_point(n), k, "split point of {n}");
+        }
+    }
+
+    /// Merkle tree hash computed straight from the RFC 6962 definition.
+    fn reference_root(leaves: &[Hash]) -> Hash {
+        match leaves.len() {
+            0 => Sha256::digest(b"").into(),
+            1 => leaves[0],
+            n => {
+                let k = split_point(n);
+                node_hash(&reference_root(&leaves[..k]), &reference_root(&leaves[k..]))
+            }
+        }
+    }
+
+    #[test]
+    fn roots_match_the_test_vectors() {
+        assert_eq!(hex::encode(MerkleTree::default().root(0)), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+        let tree = MerkleTree::new(&leaves(8));
+        for (n, root) in ROOTS.iter().enumerate() {
+            assert_eq!(hex::encode(tree.root(n + 1)), *root, "root of {} leaves", n + 1);
+        }
+    }
+
+    #[test]
+    fn cached_subtrees_give_the_same_roots() {
+        let leaves = leaves(70);
+        let mut tree = MerkleTree::default();
+        for (n, leaf) in leaves.iter().enumerate() {
+            tree.push(*leaf);
+            assert_eq!(tree.len(), n + 1);
+            for size in 0..=tree.len() {
+                assert_eq!(tree.root(size), reference_root(&leaves[..size]), "root of {size} after {} pushes", n + 1);
+            }
+        }
+    }
+
+    #[test]
+    fn audit_paths_match_the_test_vectors() {
+        let leaves = leaves(8);
+        let tree = MerkleTree::new(&leaves);
+        for (index, size, path) in PATHS {
+            let expected: Vec<Hash> = path.iter().map(|node| hash(node)).collect();
+            assert_eq!(tree.audit_path(index, size), expected, "path of {index} in {size}");
+            assert!(verify_inclusion(index as u64, size as u64, &leaves[index], &expected, &hash(ROOTS[size - 1])));
+        }
+    }
+
+    #[test]
+    fn every_leaf_is_proven_in_every_tree_size() {
+        let leaves = leaves(70);
+        let tree = MerkleTree::new(&leaves);
+        for size in 1..=leaves.len() {
+            let root = tree.root(size);
+            for (index, leaf) in leaves[..size].iter().enumerate() {
+                let path = tree.audit_path(index, size);
+                assert!(
+                    verify_inclusion(index as u64, size as u64, leaf, &path, &root),
+                    "leaf {index} of {size}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn wrong_proofs_are_refused() {
+        let leaves = leaves(7);
+        let tree = MerkleTree::new(&leaves);
+        let root = tree.root(7);
+        let path = tree.audit_path(3, 7);
+        assert!(verify_inclusion(3, 7, &leaves[3], &path, &root));
+        assert!(!verify_inclusion(2, 7, &leaves[3], &path, &root), "wrong index");
+        let last = tree.audit_path(6, 7);
+        assert!(verify_inclusion(6, 7, &leaves[6], &last, &root));
+        assert!(!verify_inclusion(6, 8, &leaves[6], &last, &root), "wrong tree size");
+        assert!(!verify_inclusion(7, 7, &leaves[3], &path, &root), "index past the tree");
+        assert!(!verify_inclusion(3, 7, &leaves[4], &path, &root), "wrong leaf");
+        assert!(!verify_inclusion(3, 7, &leaves[3], &path[..path.len() - 1], &root), "short path");
+        let mut long = path.clone();
+        long.push(root);
+        assert!(!verify_inclusion(3, 7, &leaves[3], &long, &root), "long path");
+        let mut tampered = path.clone();
+        tampered[0][0] ^= 1;
+        assert!(!verify_inclusion(3, 7, &leaves[3], &tampered, &root), "tampered path");
+        assert!(!verify_inclusion(3, 7, &leaves[3], &path, &tree.root(6)), "wrong root");
+    }
+
+    #[test]
+    fn consistency_proofs_match_the_test_vectors() {
+        let tree = MerkleTree::new(&leaves(8));
+        for (first, second, proof) in CONSISTENCY {
+            let expected: Vec<Hash> = proof.iter().map(|node| hash(node)).collect();
+            assert_eq!(tree.consistency_proof(first, second), expected, "proof from {first} to {second}");
+            assert!(verify_consistency(first as u64, second as u64, &hash(ROOTS[first - 1]), &hash(ROOTS[second - 1]), &expected));
+        }
+    }
+
+    #[test]
+    fn every_tree_size_is_consistent_with_every_larger_one() {
+        let tree = MerkleTree::new(&leaves(40));
+        for second in 1..=tree.len() {
+            for first in 1..=second {
+                let proof = tree.consistency_proof(first, second);
+                assert!(
+                    verify_consistency(first as u64, second as u64, &tree.root(first), &tree.root(second), &proof),
+                    "from {first} to {second}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn wrong_consistency_proofs_are_refused() {
+        let tree = MerkleTree::new(&leaves(8));
+        let (old, new) = (tree.root(6), tree.root(8));
+        let proof = tree.consistency_proof(6, 8);
+        assert!(verify_consistency(6, 8, &old, &new, &proof));
+        assert!(!verify_consistency(5, 8, &old, &new, &proof), "wrong first size");
+        assert!(!verify_consistency(6, 9, &old, &new, &proof), "wrong second size");
+        assert!(!verify_consistency(0, 8, &old, &new, &proof), "empty first tree");
+        assert!(!verify_consistency(8, 6, &new, &old, &proof), "shrinking tree");
+        assert!(!verify_consistency(6, 8, &tree.root(5), &new, &proof), "wrong old root");
+        assert!(!verify_consistency(6, 8, &old, &tree.root(7), &proof), "wrong new root");
+        assert!(!verify_consistency(6, 8, &old, &new, &proof[..2]), "short proof");
+        let mut tampered = proof.clone();
+        tampered[1][0] ^= 1;
+        assert!(!verify_consistency(6, 8, &old, &new, &tampered), "tampered proof");
+        assert!(!verify_consistency(8, 8, &new, &new, &proof), "proof for equal sizes");
+        assert!(verify_consistency(8, 8, &new, &new, &[]));
+        // Rewriting an old leaf gives a tree of 8 that doesn't extend the old one.
+        let mut forked = leaves(8);
+        forked[3][0] ^= 1;
+        let forked = MerkleTree::new(&forked);
+        assert!(!verify_consistency(6, 8, &old, &forked.root(8), &forked.consistency_proof(6, 8)), "forked history");
+    }
+
+    #[test]
+    fn tree_heads_are_verified_with_the_log_key() {
+        let secret = SecretKey::from_bytes(&[7; 32]).unwrap();
+        let keypair = Keypair { public: PublicKey::from(&secret), secret };
+        let signer = LogSigner(Some(keypair));
+        let head = sign_head(&signer, &MerkleTree::new(&leaves(5))).unwrap();
+        assert_eq!(head.tree_size, 5);
+        assert_eq!(head.root_hash, ROOTS[4]);
+        let public = signer.0.as_ref().unwrap().public;
+        assert!(verify_tree_head(&head, &public));
+
+        let other = SecretKey::from_bytes(&[8; 32]).unwrap();
+        assert!(!verify_tree_head(&head, &PublicKey::from(&other)), "another key");
+        assert!(!verify_tree_head(&TreeHead { tree_size: 4, ..head.clone() }, &public), "another size");
+        assert!(!verify_tree_head(&TreeHead { timestamp: head.timestamp + 1, ..head.clone() }, &public), "another time");
+        assert!(!verify_tree_head(&TreeHead { root_hash: ROOTS[3].into(), ..head.clone() }, &public), "another root");
+        assert!(!verify_tree_head(&TreeHead { signature: "not base64".into(), ..head }, &public), "malformed signature");
+    }
+}
@@ -1,54 +1,153 @@
 
-use rocket::response::status::Unauthorized;
+use prisma_client_rust::Direction;
+use rocket::State;
 use rocket::serde::json::Json;
+use rocket::serde::{Deserialize, Serialize};
 use crate::Session;
-use crate::db::{establish_connection, prisma::user};
+use crate::auth::IdentityInfo;
+use crate::db::prisma::{identity, moderation_action, report, signing_key, user, PrismaClient};
 use crate::error::*;
+use crate::moderation::Member;
+use crate::reports::OwnReport;
+use crate::sessions::{self, Redis, SessionListing};
+use crate::signing::{self, KeyInfo};
 
 #[get("/user")]
-pub async fn get_user(session: Session<'_>) -> Result<Json<user::Data>, Unauthorized<Json<Error>>>{
-    match session.get().await {
-        Ok(Some(session)) => {
-            let client = establish_connection().await;
-            match client {
-                Ok(client) => {
-                    let user = client.user().find_unique(user::id::equals(session.id as i64)).exec().await.ok();
-                    if let Some(Some(user)) = user {
-                        Ok(Json(user))   
-                    } else {
-                        Err(Unauthorized(Some(Json(Error {
-                            kind: ErrorKind::NotLoggedIn,
-                            action: "Send a `token` cookie.".into(),
-                            message: "Unauthorized".into()
-                        }))))
-                    }
-                }
-                Err(err) => {
-                    Err(Unauthorized(Some(Json(err))))
-                }
-            }
-        }
-        _ => {
-            Err(Unauthorized(Some(Json(Error {
-                kind: ErrorKind::NotLoggedIn,
-                action: "Send a `token` cookie.".into(),
-                message: "Unauthorized".into()
-            }))))
+pub async fn get_user(session: Session<'_>, client: &State<PrismaClient>) -> Result<Json<user::Data>, Error> {
+    let session = session.get().await.ok().flatten().ok_or_else(Error::not_logged_in)?;
+    let user = client.user()
+        .find_unique(user::id::equals(session.id as i64))
+        .exec().await
+        .map_err(Error::database)?
+        .ok_or_else(Error::not_logged_in)?;
+    Ok(Json(user))
+}
+#[delete("/session")] 
+pub async fn logout(session: Session<'_>, redis: &State<Redis>) -> Result<(), Error> {
+    match session.get().await.ok().flatten() {
+        Some(info) => {
+            sessions::revoke(redis, info.id, &info.sid).await.ok();
+            session.remove().await
+                .map_err(Error::database)
         }
+        None => Err(Error::new(ErrorKind::NotLoggedIn, "Send a `token` cookie.", "You're already logged out"))
     }
-    
-    
 }
-#[delete("/session")] 
-pub async fn logout(session: Session<'_>) -> Result<(), Unauthorized<Json<Error>>> {
-    if let None = session.get().await.unwrap(){
-        Err(Unauthorized(Some(Json(Error {
-            kind: ErrorKind::NotLoggedIn,
-            action: "Send a `token` cookie.".into(),
-            message: "You're already logged out".into()
-        }))))
-    } else {
-        session.remove().await.unwrap();
-        Ok(())
+
+/// Fields of a user anyone can see.
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct PublicProfile {
+    pub id: i64,
+    pub name: String,
+    pub username: String,
+    pub avatar_url: String,
+    pub joined_at: String
+}
+
+#[get("/users/<name>")]
+pub async fn get_public_user(name: &str, client: &State<PrismaClient>) -> Result<Json<PublicProfile>, Error> {
+    let user = client.user()
+        .find_unique(user::name::equals(name.to_string()))
+        .exec().await
+        .map_err(Error::database)?;
+    match user {
+        Some(user) if user.banned_at.is_none() => Ok(Json(PublicProfile {
+            id: user.id,
+            name: user.name,
+            username: user.username,
+            avatar_url: user.avatar_url,
+            joined_at: user.created_at.to_rfc3339()
+        })),
+        _ => Err(Error::new(ErrorKind::NotFound, "Check the name", "There's no user with this name"))
+    }
+}
+
+/// Everything the registry stores about a user, for `GET /api/user/export`.
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct PersonalData {
+    pub profile: user::Data,
+    pub identities: Vec<IdentityInfo>,
+    pub signing_keys: Vec<KeyInfo>,
+    pub reports: Vec<OwnReport>,
+    /// Actions the user took as a moderator.
+    pub moderation_actions: Vec<moderation_action::Data>,
+    /// Actions moderators took on the user, such as bans.
+    pub moderation_received: Vec<moderation_action::Data>,
+    pub sessions: Vec<SessionListing>
+}
+
+#[get("/user/export")]
+pub async fn export_data(member: Result<Member, Error>, session: Session<'_>, redis: &State<Redis>, client: &State<PrismaClient>) -> Result<Json<PersonalData>, Error> {
+    let Member(profile) = member?;
+    let identities = client.identity()
+        .find_many(vec![identity::user_id::equals(profile.id)])
+        .exec().await
+        .map_err(Error::database)?;
+    let signing_keys = client.signing_key()
+        .find_many(vec![signing_key::user_id::equals(profile.id)])
+        .exec().await
+        .map_err(Error::database)?;
+    let reports = client.report()
+        .find_many(vec![report::reporter_id::equals(Some(profile.id))])
+        .exec().await
+        .map_err(Error::database)?;
+    let moderation_actions = client.moderation_action()
+        .find_many(vec![moderation_action::actor_id::equals(profile.id)])
+        .exec().await
+        .map_err(Error::database)?;
+    let moderation_received = client.moderation_action()
+        .find_many(vec![moderation_action::target::equals(format!("user:{}", profile.id))])
+        .order_by(moderation_action::id::order(Direction::Asc))
+        .exec().await
+        .map_err(Error::database)?;
+    let sessions = sessions::listings(redis, profile.id as u64, &session).await?;
+    Ok(Json(PersonalData {
+        profile,
+        identities: identities.into_iter().map(IdentityInfo::from).collect(),
+        signing_keys: signing_keys.into_iter().map(KeyInfo::from).collect(),
+        reports: reports.into_iter().map(OwnReport::from).collect(),
+        moderation_actions,
+        moderation_received,
+        sessions
+    }))
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct DeleteAccount {
+    /// Must repeat the account's name, to avoid deleting it by accident.
+    pub confirm: String
+}
+
+/// Deletes the account and everything attached to it. Reports filed by the
+/// user are kept without their author, and key removals are appended to the
+/// transparency log, which itself can't be erased.
+#[delete("/user", data = "<body>")]
+pub async fn delete_account(member: Result<Member, Error>, session: Session<'_>, redis: &State<Redis>, body: Json<DeleteAccount>, client: &State<PrismaClient>) -> Result<(), Error> {
+    let Member(account) = member?;
+    if body.confirm != account.name {
+        return Err(Error::new(
+            ErrorKind::ValidationError,
+            "Send your name in `confirm`",
+            "The confirmation doesn't match your name"
+        ));
     }
-}
\ No newline at end of file
+    let keys = client.signing_key()
+        .find_many(vec![signing_key::user_id::equals(account.id)])
+        .exec().await
+        .map_err(Error::database)?;
+    // Each key goes with its log entry, so a failure part way leaves the
+    // remaining keys logged as they are, and deleting again finishes the job.
+    for key in keys {
+        signing::remove(client, account.id, key.fingerprint).await?;
+    }
+    client.user()
+        .delete(user::id::equals(account.id))
+        .exec().await
+        .map_err(Error::database)?;
+    sessions::revoke_all(redis, account.id as u64).await.ok();
+    session.remove().await.ok();
+    Ok(())
+}
@@ -0,0 +1,159 @@
+mod common;
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::PathBuf;
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use rand::Rng;
+use common::Registry;
+use server::archive::{self, Manifest};
+use server::db::prisma::{report, user, PrismaClient, ReportCategory};
+
+fn archive_path() -> PathBuf {
+    std::env::temp_dir().join(format!("registry-{:016x}.tar.gz", rand::thread_rng().gen::<u64>()))
+}
+
+/// A migrated database the archive is imported into, emptied first. Its
+/// tables are truncated since the rules of LogEntry refuse deletes.
+async fn import_database() -> Option<PrismaClient> {
+    let url = match std::env::var("TEST_IMPORT_DATABASE_URL") {
+        Ok(url) => url,
+        Err(_) => {
+            eprintln!("TEST_IMPORT_DATABASE_URL isn't set, skipping");
+            return None;
+        }
+    };
+    let client = server::db::establish_connection(&url).await.expect("can't connect to the import database");
+    client._execute_raw(prisma_client_rust::raw!(
+        r#"TRUNCATE "User", "Identity", "SigningKey", "LogEntry", "LockedName", "ModerationAction", "Report" CASCADE"#
+    ))
+        .exec().await
+        .expect("can't empty the import database");
+    Some(client)
+}
+
+fn read_archive(path: &PathBuf) -> Vec<(String, Vec<u8>)> {
+    let mut archive = tar::Archive::new(GzDecoder::new(File::open(path).unwrap()));
+    archive.entries().unwrap().map(|entry| {
+        let mut entry = entry.unwrap();
+        let name = entry.path().unwrap().to_string_lossy().into_owned();
+        let mut data = vec![];
+        entry.read_to_end(&mut data).unwrap();
+        (name, data)
+    }).collect()
+}
+
+fn write_archive(path: &PathBuf, files: &[(String, Vec<u8>)]) {
+    let mut archive = tar::Builder::new(GzEncoder::new(File::create(path).unwrap(), Compression::default()));
+    for (name, data) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        archive.append_data(&mut header, name, data.as_slice()).unwrap();
+    }
+    archive.into_inner().unwrap().finish().unwrap();
+}
+
+fn digests(manifest: &Manifest) -> HashMap<&str, &str> {
+    manifest.files.iter().map(|file| (file.path.as_str(), file.sha256.as_str())).collect()
+}
+
+#[rocket::async_test]
+async fn an_export_imports_into_the_same_registry() {
+    let registry = match Registry::start().await {
+        Some(registry) => registry,
+        None => return
+    };
+    let target = match import_database().await {
+        Some(target) => target,
+        None => return registry.cleanup().await
+    };
+    registry.login().await;
+    let db = registry.db().await;
+    let plugin = format!("archive-test-{}", registry.user.id);
+    // A deleted report leaves a gap in the ids, which the import keeps.
+    let create = || db.report().create(plugin.clone(), ReportCategory::Spam, "Spam".into(), vec![]).exec();
+    let deleted = create().await.expect("can't create a report");
+    let kept = create().await.expect("can't create a report");
+    db.report().delete(report::id::equals(deleted.id)).exec().await.expect("can't delete a report");
+
+    let exported = archive_path();
+    let manifest = archive::export(&db, &exported).await.expect("the export failed");
+    let imported = archive::import(&target, &exported).await.expect("the import failed");
+    assert_eq!(digests(&imported), digests(&manifest));
+
+    let copy = target.report().find_unique(report::id::equals(kept.id)).exec().await.unwrap().expect("the report changed id");
+    assert_eq!(copy.plugin, plugin);
+    let next = target.report().create("next".into(), ReportCategory::Spam, "Spam".into(), vec![]).exec().await.unwrap();
+    assert!(next.id > kept.id, "the report id sequence wasn't moved past the imported ids");
+    let name = format!("archive-test-next-{}", registry.user.id);
+    let new_user = target.user().create(name, String::new(), String::new(), vec![]).exec().await.unwrap();
+    let max_id = target.user().find_many(vec![user::id::not(new_user.id)]).exec().await.unwrap()
+        .into_iter().map(|user| user.id).max().unwrap_or(0);
+    assert!(new_user.id > max_id, "the user id sequence wasn't moved past the imported ids");
+
+    // Exporting the copy gives back the same files.
+    target.report().delete(report::id::equals(next.id)).exec().await.unwrap();
+    target.user().delete(user::id::equals(new_user.id)).exec().await.unwrap();
+    let reexported = archive_path();
+    let manifest_again = archive::export(&target, &reexported).await.expect("the second export failed");
+    assert_eq!(digests(&manifest_again), digests(&manifest));
+
+    let err = archive::import(&target, &exported).await.expect_err("imported into a database that isn't empty");
+    assert!(err.to_string().contains("isn't empty"), "{}", err);
+
+    std::fs::remove_file(exported).ok();
+    std::fs::remove_file(reexported).ok();
+    db.report().delete_many(vec![report::plugin::equals(plugin)]).exec().await.ok();
+    registry.cleanup().await;
+}
+
+#[rocket::async_test]
+async fn a_tampered_archive_is_refused() {
+    let registry = match Registry::start().await {
+        Some(registry) => registry,
+        None => return
+    };
+    registry.login().await;
+    let db = registry.db().await;
+    let exported = archive_path();
+    archive::export(&db, &exported).await.expect("the export failed");
+    let files = read_archive(&exported);
+
+    // A file changed after the export no longer matches the manifest.
+    let tampered = archive_path();
+    let mut changed = files.clone();
+    for (name, data) in &mut changed {
+        if name == "users.json" {
+            data.extend_from_slice(b" ");
+        }
+    }
+    write_archive(&tampered, &changed);
+    let err = archive::import(&db, &tampered).await.expect_err("imported a tampered file");
+    assert!(err.to_string().contains("users.json doesn't match its digest"), "{}", err);
+
+    // So doesn't an unchanged file with a changed digest.
+    let mut changed = files.clone();
+    for (name, data) in &mut changed {
+        if name == "manifest.json" {
+            let mut manifest: Manifest = rocket::serde::json::from_slice(data).unwrap();
+            for file in &mut manifest.files {
+                if file.path == "reports.json" {
+                    file.sha256 = "0".repeat(64);
+                }
+            }
+            *data = rocket::serde::json::to_string(&manifest).unwrap().into_bytes();
+        }
+    }
+    write_archive(&tampered, &changed);
+    let err = archive::import(&db, &tampered).await.expect_err("imported a file with a bad digest");
+    assert!(err.to_string().contains("reports.json doesn't match its digest"), "{}", err);
+
+    std::fs::remove_file(exported).ok();
+    std::fs::remove_file(tampered).ok();
+    registry.cleanup().await;
+}
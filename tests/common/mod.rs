@@ -0,0 +1,252 @@
+//! Test harness: the registry from `server::build`, wired to a throwaway
+//! Postgres and Redis and to a fake GitHub running on a local port.
+//!
+//! The databases come from `TEST_DATABASE_URL` and `TEST_REDIS_URL`; the
+//! migrations must already be applied (`DATABASE_URL=$TEST_DATABASE_URL
+//! cargo prisma migrate deploy`). Tests are skipped when those variables aren't set, so
+//! `cargo test` keeps working without the services.
+// Each test file only uses part of the harness.
+#![allow(dead_code)]
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use rand::Rng;
+use rocket::{Build, Config, FromForm, Rocket, Shutdown};
+use rocket::fairing::AdHoc;
+use rocket::form::Form;
+use rocket::http::{Header, Status};
+use rocket::local::asynchronous::Client;
+use rocket::serde::json::{json, Json, Value};
+use rocket::tokio::sync::oneshot;
+use server::db::prisma::{identity, user, PrismaClient};
+
+pub const ACCESS_TOKEN: &str = "fake-github-token";
+
+/// The GitHub account the fake GitHub logs everyone in as.
+#[derive(Clone, Debug)]
+pub struct FakeUser {
+    pub id: i64,
+    pub login: String,
+    pub name: String
+}
+
+impl FakeUser {
+    /// A user with a random id, so runs don't collide in a shared database.
+    pub fn random() -> Self {
+        let id = rand::thread_rng().gen_range(1_000_000_000..2_000_000_000);
+        Self { id, login: format!("test-user-{id}"), name: format!("Test User {id}") }
+    }
+
+    fn to_json(&self) -> Value {
+        let url = format!("https://api.github.com/users/{}", self.login);
+        json!({
+            "login": self.login,
+            "id": self.id,
+            "node_id": "MDQ6VXNlcjE=",
+            "avatar_url": format!("https://avatars.githubusercontent.com/u/{}", self.id),
+            "gravatar_id": "",
+            "url": url,
+            "html_url": format!("https://github.com/{}", self.login),
+            "followers_url": format!("{url}/followers"),
+            "following_url": format!("{url}/following{{/other_user}}"),
+            "gists_url": format!("{url}/gists{{/gist_id}}"),
+            "starred_url": format!("{url}/starred{{/owner}}{{/repo}}"),
+            "subscriptions_url": format!("{url}/subscriptions"),
+            "organizations_url": format!("{url}/orgs"),
+            "repos_url": format!("{url}/repos"),
+            "events_url": format!("{url}/events{{/privacy}}"),
+            "received_events_url": format!("{url}/received_events"),
+            "type": "User",
+            "site_admin": false,
+            "name": self.name,
+            "company": null,
+            "blog": "",
+            "location": null,
+            "email": null,
+            "hireable": null,
+            "bio": null,
+            "public_repos": 0,
+            "public_gists": 0,
+            "followers": 0,
+            "following": 0,
+            "created_at": "2020-01-01T00:00:00Z",
+            "updated_at": "2020-01-01T00:00:00Z"
+        })
+    }
+}
+
+#[derive(FromForm)]
+struct TokenRequest<'r> {
+    code: &'r str
+}
+
+#[rocket::post("/login/oauth/access_token", data = "<form>")]
+fn access_token(form: Form<TokenRequest<'_>>) -> Option<Json<Value>> {
+    (!form.code.is_empty()).then(|| Json(json!({
+        "access_token": ACCESS_TOKEN,
+        "token_type": "bearer",
+        "scope": "read:user"
+    })))
+}
+
+#[rocket::get("/user")]
+fn authenticated_user(user: &rocket::State<FakeUser>) -> Json<Value> {
+    Json(user.to_json())
+}
+
+/// Tokens that were revoked.
+#[derive(Clone, Default)]
+pub struct Revoked(Arc<Mutex<Vec<String>>>);
+
+#[rocket::delete("/applications/<_client_id>/token", data = "<body>")]
+fn revoke_token(_client_id: &str, body: Json<Value>, revoked: &rocket::State<Revoked>) -> Status {
+    match body["access_token"].as_str() {
+        Some(token) => {
+            revoked.0.lock().unwrap().push(token.to_string());
+            Status::NoContent
+        }
+        None => Status::UnprocessableEntity
+    }
+}
+
+/// A fake GitHub answering the OAuth token exchange, `GET /user` and token
+/// revocations.
+pub struct FakeGitHub {
+    pub url: String,
+    revoked: Revoked,
+    shutdown: Shutdown
+}
+
+impl FakeGitHub {
+    pub async fn start(user: FakeUser) -> Self {
+        // Rocket doesn't report the port it got for 0, so pick a free one.
+        let port = TcpListener::bind("127.0.0.1:0").and_then(|listener| listener.local_addr())
+            .expect("no free port").port();
+        let config = Config {
+            port,
+            address: [127, 0, 0, 1].into(),
+            log_level: rocket::config::LogLevel::Off,
+            ..Config::debug_default()
+        };
+        let (ready, started) = oneshot::channel();
+        let revoked = Revoked::default();
+        let rocket = rocket::custom(config)
+            .mount("/", rocket::routes![access_token, authenticated_user, revoke_token])
+            .manage(user)
+            .manage(revoked.clone())
+            .attach(AdHoc::on_liftoff("Ready", |rocket| Box::pin(async move {
+                ready.send(rocket.shutdown()).ok();
+            })));
+        rocket::tokio::spawn(rocket.launch());
+        let shutdown = started.await.expect("the fake GitHub didn't start");
+        Self { url: format!("http://127.0.0.1:{port}"), revoked, shutdown }
+    }
+}
+
+impl Drop for FakeGitHub {
+    fn drop(&mut self) {
+        self.shutdown.clone().notify();
+    }
+}
+
+/// A registry logged in through the fake GitHub as `user`.
+pub struct Registry {
+    pub client: Client,
+    pub user: FakeUser,
+    database_url: String,
+    github: FakeGitHub
+}
+
+impl Registry {
+    /// `None` when the test databases aren't configured.
+    pub async fn start() -> Option<Self> {
+        let (database_url, redis_url) = match (std::env::var("TEST_DATABASE_URL"), std::env::var("TEST_REDIS_URL")) {
+            (Ok(database_url), Ok(redis_url)) => (database_url, redis_url),
+            _ => {
+                eprintln!("TEST_DATABASE_URL and TEST_REDIS_URL aren't set, skipping");
+                return None;
+            }
+        };
+        let user = FakeUser::random();
+        let github = FakeGitHub::start(user.clone()).await;
+        let rocket = registry(&database_url, &redis_url, &github.url);
+        let client = Client::tracked(rocket).await.expect("the registry didn't ignite");
+        Some(Self { client, user, database_url, github })
+    }
+
+    /// Goes through `/login/github` and `/auth/github` like a browser would.
+    pub async fn login(&self) {
+        let status = self.try_login().await;
+        assert!(status.class().is_redirection(), "login failed with {status}");
+    }
+
+    /// Same as [`Self::login`], but returns the status of the callback
+    /// instead of expecting it to succeed.
+    pub async fn try_login(&self) -> Status {
+        self.through_github("/login/github").await
+    }
+
+    /// Links the fake GitHub account to the logged in user, returning the
+    /// status of the callback.
+    pub async fn try_link(&self) -> Status {
+        self.through_github("/login/github?link=true").await
+    }
+
+    async fn through_github(&self, start: &str) -> Status {
+        let login = self.client.get(start).dispatch().await;
+        let location = login.headers().get_one("Location").expect("no redirect to GitHub").to_string();
+        let state = query_param(&location, "state").expect("no OAuth state");
+        let callback = self.client.get(format!("/auth/github?code=fake-code&state={state}")).dispatch().await;
+        callback.status()
+    }
+
+    /// Registry id of the logged in user.
+    pub async fn user_id(&self) -> i64 {
+        let user: Value = self.client.get("/api/user").dispatch().await.into_json().await.expect("not logged in");
+        user["id"].as_i64().expect("the user has no id")
+    }
+
+    /// Tokens the registry revoked.
+    pub fn revoked_tokens(&self) -> Vec<String> {
+        self.github.revoked.0.lock().unwrap().clone()
+    }
+
+    /// A connection to the registry's database.
+    pub async fn db(&self) -> PrismaClient {
+        server::db::establish_connection(&self.database_url).await.expect("can't connect to the test database")
+    }
+
+    /// Removes the test user, and its identities, signing keys and reports
+    /// with it.
+    pub async fn cleanup(self) {
+        if let Ok(db) = server::db::establish_connection(&self.database_url).await {
+            let identity = format!("github:{}", self.user.id);
+            db.user()
+                .delete_many(vec![user::identities::some(vec![identity::id::equals(identity)])])
+                .exec().await.ok();
+        }
+    }
+}
+
+fn registry(database_url: &str, redis_url: &str, github_url: &str) -> Rocket<Build> {
+    let figment = server::config::figment()
+        .merge(("database_url", database_url))
+        .merge(("redis_url", redis_url))
+        .merge(("gh_client_id", "test-client"))
+        .merge(("gh_client_secret", "test-secret"))
+        .merge(("gh_redirect_url", "http://localhost/auth/github"))
+        .merge(("gh_url", github_url))
+        .merge(("gh_api_url", github_url))
+        .merge(("log_level", "off"));
+    server::build(figment)
+}
+
+fn query_param<'a>(url: &'a str, name: &str) -> Option<&'a str> {
+    let (_, query) = url.split_once('?')?;
+    query.split('&').find_map(|pair| pair.strip_prefix(name)?.strip_prefix('='))
+}
+
+/// Headers a same-origin browser request carries, needed for requests that
+/// change data with the session cookie.
+pub fn same_origin() -> [Header<'static>; 2] {
+    [Header::new("Host", "localhost"), Header::new("Origin", "http://localhost")]
+}
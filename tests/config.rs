@@ -0,0 +1,61 @@
+use rocket::figment::Figment;
+use server::config::Config;
+
+fn figment() -> Figment {
+    Figment::new()
+        .merge(("database_url", "postgres://localhost/registry"))
+        .merge(("gh_client_id", "client"))
+        .merge(("gh_client_secret", "secret"))
+}
+
+#[test]
+fn zero_rate_limits_are_refused() {
+    for limit in ["0/60", "10/0"] {
+        let err = Config::from_figment(&figment().merge(("rate_limit_login", limit))).expect_err(limit);
+        assert!(err.0.iter().any(|problem| problem.starts_with("RATE_LIMIT_LOGIN")), "{:?}", err.0);
+    }
+    let config = Config::from_figment(&figment().merge(("rate_limit_login", "10/60"))).expect("a valid limit is refused");
+    assert_eq!(config.rate_limits["login"].requests, 10);
+}
+
+#[test]
+fn the_client_ip_header_is_optional() {
+    let config = Config::from_figment(&figment()).expect("the config is refused");
+    assert_eq!(config.client_ip_header, None);
+    let config = Config::from_figment(&figment().merge(("client_ip_header", "X-Forwarded-For"))).expect("the config is refused");
+    assert_eq!(config.client_ip_header.as_deref(), Some("X-Forwarded-For"));
+}
+
+#[test]
+fn the_example_env_is_valid() {
+    let mut figment = Figment::new();
+    for item in dotenvy::from_filename_iter(".env.example").expect("can't read .env.example") {
+        let (key, value) = item.expect("invalid line in .env.example");
+        figment = figment.merge((key.to_lowercase(), value));
+    }
+    if let Err(err) = Config::from_figment(&figment) {
+        panic!("{}", err);
+    }
+}
+
+#[test]
+fn every_problem_is_reported_at_once() {
+    let figment = Figment::new()
+        .merge(("gh_client_id", "client"))
+        .merge(("gh_client_secret", vec!["not", "a", "string"]))
+        .merge(("redis_url", "not a url"))
+        .merge(("dev_login", "maybe"))
+        .merge(("rate_limit_search", "fast"));
+    let err = Config::from_figment(&figment).expect_err("a broken config is accepted");
+    for key in ["DATABASE_URL", "GH_CLIENT_SECRET", "REDIS_URL", "DEV_LOGIN", "RATE_LIMIT_SEARCH"] {
+        assert!(err.0.iter().any(|problem| problem.starts_with(key)), "{key} isn't reported: {:?}", err.0);
+    }
+}
+
+#[test]
+fn numbers_and_booleans_are_read_as_strings() {
+    let config = Config::from_figment(&figment().merge(("metrics_token", 1234)).merge(("dev_login", true)))
+        .expect("the config is refused");
+    assert_eq!(config.metrics_token.as_deref(), Some("1234"));
+    assert!(config.dev_login);
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use rocket::http::{ContentType, Status};
+use rocket::serde::json::Value;
+use common::{same_origin, Registry};
+
+#[rocket::async_test]
+async fn a_fresh_registry_is_ready() {
+    let registry = match Registry::start().await {
+        Some(registry) => registry,
+        None => return
+    };
+    let readyz = registry.client.get("/readyz").dispatch().await;
+    assert_eq!(readyz.status(), Status::Ok);
+    let readiness: Value = readyz.into_json().await.expect("the readiness isn't JSON");
+    assert_eq!(readiness["storage"]["ok"], true);
+    registry.cleanup().await;
+}
+
+#[rocket::async_test]
+async fn unhandled_api_errors_are_json() {
+    let registry = match Registry::start().await {
+        Some(registry) => registry,
+        None => return
+    };
+    registry.login().await;
+
+    let missing = registry.client.get("/api/no-such-route").dispatch().await;
+    assert_eq!(missing.status(), Status::NotFound);
+    let error: Value = missing.into_json().await.expect("the error isn't JSON");
+    assert_eq!(error["kind"], "NotFound");
+    assert!(error["request_id"].is_string());
+
+    let [host, origin] = same_origin();
+    let malformed = registry.client.post("/api/reports")
+        .header(host).header(origin)
+        .header(ContentType::JSON)
+        .body("{ not json")
+        .dispatch().await;
+    assert!(malformed.status().class().is_client_error(), "malformed JSON got {}", malformed.status());
+    let error: Value = malformed.into_json().await.expect("the error isn't JSON");
+    assert_eq!(error["kind"], "ValidationError");
+    assert!(error["request_id"].is_string());
+    registry.cleanup().await;
+}
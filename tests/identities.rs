@@ -0,0 +1,148 @@
+mod common;
+
+use rocket::http::Status;
+use rocket::serde::json::Value;
+use common::{same_origin, Registry};
+use server::db::prisma::{identity, user};
+
+#[rocket::async_test]
+async fn the_last_identity_cannot_be_unlinked() {
+    let registry = match Registry::start().await {
+        Some(registry) => registry,
+        None => return
+    };
+    registry.login().await;
+
+    let identities = registry.client.get("/api/user/identities").dispatch().await;
+    assert_eq!(identities.status(), Status::Ok);
+    let identities: Value = identities.into_json().await.expect("the identities aren't JSON");
+    let identities = identities.as_array().expect("the identities aren't a list");
+    assert_eq!(identities.len(), 1);
+    let id = format!("github:{}", registry.user.id);
+    assert_eq!(identities[0]["id"], id.as_str());
+    assert_eq!(identities[0]["login"], registry.user.login.as_str());
+
+    let [host, origin] = same_origin();
+    let unlink = registry.client.delete(format!("/api/user/identities/{id}")).header(host).header(origin).dispatch().await;
+    assert_eq!(unlink.status(), Status::Conflict);
+
+    let user = registry.client.get("/api/user").dispatch().await;
+    assert_eq!(user.status(), Status::Ok);
+    registry.cleanup().await;
+}
+
+#[rocket::async_test]
+async fn logging_in_again_finds_the_same_user() {
+    let registry = match Registry::start().await {
+        Some(registry) => registry,
+        None => return
+    };
+    registry.login().await;
+    let first: Value = registry.client.get("/api/user").dispatch().await.into_json().await.expect("the user isn't JSON");
+    registry.login().await;
+    let second: Value = registry.client.get("/api/user").dispatch().await.into_json().await.expect("the user isn't JSON");
+    assert_eq!(first["id"], second["id"]);
+    registry.cleanup().await;
+}
+
+#[rocket::async_test]
+async fn a_new_user_doesnt_take_an_existing_name() {
+    let registry = match Registry::start().await {
+        Some(registry) => registry,
+        None => return
+    };
+    let db = registry.db().await;
+    let holder = db.user()
+        .create(registry.user.login.clone(), "Someone else".into(), String::new(), vec![])
+        .exec().await
+        .expect("can't create the user holding the name");
+    registry.login().await;
+    let user: Value = registry.client.get("/api/user").dispatch().await.into_json().await.expect("the user isn't JSON");
+    assert_eq!(user["name"], format!("{}-github", registry.user.login).as_str());
+    assert_ne!(user["id"], holder.id);
+
+    let profile = registry.client.get(format!("/api/users/{}", registry.user.login)).dispatch().await;
+    let profile: Value = profile.into_json().await.expect("the profile isn't JSON");
+    assert_eq!(profile["id"], holder.id);
+    db.user().delete(user::id::equals(holder.id)).exec().await.ok();
+    registry.cleanup().await;
+}
+
+#[rocket::async_test]
+async fn an_abandoned_link_doesnt_hijack_the_next_login() {
+    let registry = match Registry::start().await {
+        Some(registry) => registry,
+        None => return
+    };
+    registry.login().await;
+    let link = registry.client.get("/login/github?link=true").dispatch().await;
+    assert!(link.status().class().is_redirection());
+    // Logging in again instead of finishing the link opens a new session.
+    registry.login().await;
+    let sessions: Value = registry.client.get("/api/sessions").dispatch().await.into_json().await.expect("the sessions aren't JSON");
+    assert_eq!(sessions.as_array().expect("the sessions aren't a list").len(), 2);
+    registry.cleanup().await;
+}
+
+#[rocket::async_test]
+async fn a_linked_identity_can_be_unlinked() {
+    let registry = match Registry::start().await {
+        Some(registry) => registry,
+        None => return
+    };
+    registry.login().await;
+    // Pretend the user logged in with GitLab, so linking adds GitHub.
+    let db = registry.db().await;
+    let github = format!("github:{}", registry.user.id);
+    let gitlab = format!("gitlab:{}", registry.user.id);
+    db.identity()
+        .update(identity::id::equals(github.clone()), vec![identity::id::set(gitlab.clone()), identity::provider::set("gitlab".into())])
+        .exec().await
+        .expect("can't move the identity to GitLab");
+
+    assert!(registry.try_link().await.class().is_redirection(), "linking GitHub failed");
+    let identities: Value = registry.client.get("/api/user/identities").dispatch().await.into_json().await.expect("the identities aren't JSON");
+    let mut ids: Vec<_> = identities.as_array().expect("the identities aren't a list").iter()
+        .map(|identity| identity["id"].as_str().expect("an identity has no id").to_string())
+        .collect();
+    ids.sort();
+    assert_eq!(ids, [github.clone(), gitlab.clone()]);
+
+    let [host, origin] = same_origin();
+    let unlink = registry.client.delete(format!("/api/user/identities/{gitlab}")).header(host).header(origin).dispatch().await;
+    assert_eq!(unlink.status(), Status::Ok);
+    let identities: Value = registry.client.get("/api/user/identities").dispatch().await.into_json().await.expect("the identities aren't JSON");
+    assert_eq!(identities.as_array().expect("the identities aren't a list").len(), 1);
+    assert_eq!(identities[0]["id"], github.as_str());
+    registry.cleanup().await;
+}
+
+#[rocket::async_test]
+async fn an_identity_of_another_account_isnt_linked() {
+    let registry = match Registry::start().await {
+        Some(registry) => registry,
+        None => return
+    };
+    registry.login().await;
+    // Hand the GitHub identity to another account, as if it had logged in
+    // with it first.
+    let db = registry.db().await;
+    let id = registry.user_id().await;
+    let github = format!("github:{}", registry.user.id);
+    let other = db.user()
+        .create(format!("{}-other", registry.user.login), "Other".into(), String::new(), vec![])
+        .exec().await
+        .expect("can't create the other user");
+    db.identity()
+        .update(identity::id::equals(github.clone()), vec![identity::user::connect(user::id::equals(other.id))])
+        .exec().await
+        .expect("can't move the identity");
+
+    assert_eq!(registry.try_link().await, Status::Conflict);
+    let moved = db.identity().find_unique(identity::id::equals(github)).exec().await.unwrap().expect("the identity is gone");
+    assert_eq!(moved.user_id, other.id, "the identity changed accounts");
+
+    // The other account holds the identity now, so cleanup removes that one.
+    db.user().delete(user::id::equals(id)).exec().await.ok();
+    registry.cleanup().await;
+}
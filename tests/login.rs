@@ -0,0 +1,100 @@
+mod common;
+
+use std::net::SocketAddr;
+use rocket::http::Status;
+use rocket::serde::json::Value;
+use common::{same_origin, Registry};
+
+#[rocket::async_test]
+async fn login_then_logout() {
+    let registry = match Registry::start().await {
+        Some(registry) => registry,
+        None => return
+    };
+    registry.login().await;
+    // The token was only needed for the profile.
+    assert_eq!(registry.revoked_tokens(), vec![common::ACCESS_TOKEN.to_string()]);
+    assert!(registry.client.cookies().get("provider_token").is_none());
+
+    let user = registry.client.get("/api/user").dispatch().await;
+    assert_eq!(user.status(), Status::Ok);
+    let user: Value = user.into_json().await.expect("the user isn't JSON");
+    assert!(user["id"].is_i64());
+    assert_eq!(user["name"], registry.user.login.as_str());
+    assert_eq!(user["username"], registry.user.name.as_str());
+
+    let [host, origin] = same_origin();
+    let logout = registry.client.delete("/api/session").header(host).header(origin).dispatch().await;
+    assert_eq!(logout.status(), Status::Ok);
+
+    let user = registry.client.get("/api/user").dispatch().await;
+    assert_eq!(user.status(), Status::Unauthorized);
+    registry.cleanup().await;
+}
+
+#[rocket::async_test]
+async fn logout_refuses_cross_site_requests() {
+    let registry = match Registry::start().await {
+        Some(registry) => registry,
+        None => return
+    };
+    registry.login().await;
+
+    let logout = registry.client.delete("/api/session")
+        .header(rocket::http::Header::new("Host", "localhost"))
+        .header(rocket::http::Header::new("Origin", "https://evil.example"))
+        .dispatch().await;
+    assert_eq!(logout.status(), Status::Forbidden);
+    let error: Value = logout.into_json().await.expect("the error isn't JSON");
+    assert_eq!(error["kind"], "Forbidden");
+    assert!(error["request_id"].is_string());
+
+    // A bearer token next to the cookie doesn't make the request trusted.
+    let logout = registry.client.delete("/api/session")
+        .header(rocket::http::Header::new("Host", "localhost"))
+        .header(rocket::http::Header::new("Origin", "https://evil.example"))
+        .header(rocket::http::Header::new("Authorization", "Bearer anything"))
+        .dispatch().await;
+    assert_eq!(logout.status(), Status::Forbidden);
+
+    let user = registry.client.get("/api/user").dispatch().await;
+    assert_eq!(user.status(), Status::Ok);
+    registry.cleanup().await;
+}
+
+#[rocket::async_test]
+async fn dev_login_is_off_by_default() {
+    let registry = match Registry::start().await {
+        Some(registry) => registry,
+        None => return
+    };
+    let login = registry.client.get("/login/dev").dispatch().await;
+    assert_eq!(login.status(), Status::NotFound);
+    let user = registry.client.get("/api/user").dispatch().await;
+    assert_eq!(user.status(), Status::Unauthorized);
+    registry.cleanup().await;
+}
+
+#[rocket::async_test]
+async fn the_eleventh_login_in_a_minute_is_refused() {
+    let registry = match Registry::start().await {
+        Some(registry) => registry,
+        None => return
+    };
+    // An address of its own, so other tests don't count against the limit.
+    let ip: [u8; 4] = [10, rand::random(), rand::random(), rand::random()];
+    let remote = SocketAddr::from((ip, 4000));
+    for attempt in 1..=10 {
+        let login = registry.client.get("/login/github").remote(remote).dispatch().await;
+        assert!(login.status().class().is_redirection(), "login {attempt} got {}", login.status());
+    }
+
+    let login = registry.client.get("/login/github").remote(remote).dispatch().await;
+    assert_eq!(login.status(), Status::TooManyRequests);
+    let retry_after: u64 = login.headers().get_one("Retry-After").expect("no Retry-After").parse().expect("Retry-After isn't seconds");
+    assert!((1..=60).contains(&retry_after), "Retry-After is {retry_after}");
+    let error: Value = login.into_json().await.expect("the error isn't JSON");
+    assert_eq!(error["kind"], "RateLimited");
+    assert!(error["request_id"].is_string());
+    registry.cleanup().await;
+}
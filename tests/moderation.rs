@@ -0,0 +1,141 @@
+mod common;
+
+use rand::Rng;
+use rocket::http::Status;
+use rocket::serde::json::{json, Value};
+use common::{same_origin, Registry};
+use server::db::prisma::{moderation_action, user, PrismaClient, Role};
+
+/// A user nobody logs in as, with `role`.
+async fn user_with_role(db: &PrismaClient, role: Role) -> user::Data {
+    let name = format!("moderation-target-{:x}", rand::thread_rng().gen::<u64>());
+    db.user()
+        .create(name, "Target".into(), String::new(), vec![user::role::set(role)])
+        .exec().await
+        .expect("can't create the target user")
+}
+
+async fn set_role(db: &PrismaClient, id: i64, role: Role) {
+    db.user().update(user::id::equals(id), vec![user::role::set(role)]).exec().await.expect("can't set the role");
+}
+
+async fn post(registry: &Registry, path: String, body: Value) -> Status {
+    let [host, origin] = same_origin();
+    registry.client.post(path).header(host).header(origin).json(&body).dispatch().await.status()
+}
+
+async fn put(registry: &Registry, path: String, body: Value) -> Status {
+    let [host, origin] = same_origin();
+    registry.client.put(path).header(host).header(origin).json(&body).dispatch().await.status()
+}
+
+#[rocket::async_test]
+async fn moderators_only_ban_users_below_them() {
+    let registry = match Registry::start().await {
+        Some(registry) => registry,
+        None => return
+    };
+    registry.login().await;
+    let db = registry.db().await;
+    let actor = registry.user_id().await;
+    let target = user_with_role(&db, Role::User).await;
+    let other_moderator = user_with_role(&db, Role::Moderator).await;
+
+    let ban = format!("/api/admin/users/{}/ban", target.id);
+    assert_eq!(post(&registry, ban.clone(), json!({ "reason": "spam" })).await, Status::Forbidden, "a user banned someone");
+
+    set_role(&db, actor, Role::Moderator).await;
+    assert_eq!(post(&registry, ban.clone(), json!({ "reason": " " })).await, Status::UnprocessableEntity, "banned without a reason");
+    let other_ban = format!("/api/admin/users/{}/ban", other_moderator.id);
+    assert_eq!(post(&registry, other_ban, json!({ "reason": "spam" })).await, Status::Forbidden, "a moderator banned a moderator");
+    assert_eq!(post(&registry, ban, json!({ "reason": "spam" })).await, Status::Ok);
+
+    let banned = db.user().find_unique(user::id::equals(target.id)).exec().await.unwrap().unwrap();
+    assert!(banned.banned_at.is_some());
+    assert_eq!(banned.ban_reason.as_deref(), Some("spam"));
+    let actions = db.moderation_action()
+        .find_many(vec![moderation_action::actor_id::equals(actor)])
+        .exec().await.unwrap();
+    assert_eq!(actions.len(), 1, "only the successful ban is recorded: {:?}", actions);
+    assert_eq!(actions[0].action, "ban");
+    assert_eq!(actions[0].target, format!("user:{}", target.id));
+    assert_eq!(actions[0].reason, "spam");
+
+    db.moderation_action().delete_many(vec![moderation_action::actor_id::equals(actor)]).exec().await.ok();
+    db.user().delete_many(vec![user::id::in_vec(vec![target.id, other_moderator.id])]).exec().await.ok();
+    registry.cleanup().await;
+}
+
+#[rocket::async_test]
+async fn only_admins_change_roles() {
+    let registry = match Registry::start().await {
+        Some(registry) => registry,
+        None => return
+    };
+    registry.login().await;
+    let db = registry.db().await;
+    let actor = registry.user_id().await;
+    let target = user_with_role(&db, Role::User).await;
+    let other_admin = user_with_role(&db, Role::Admin).await;
+    let promote = format!("/api/admin/users/{}/role", target.id);
+    let body = json!({ "role": "Moderator", "reason": "helps out" });
+
+    set_role(&db, actor, Role::Moderator).await;
+    assert_eq!(put(&registry, promote.clone(), body.clone()).await, Status::Forbidden, "a moderator changed a role");
+
+    set_role(&db, actor, Role::Admin).await;
+    let demote = format!("/api/admin/users/{}/role", other_admin.id);
+    assert_eq!(put(&registry, demote, json!({ "role": "User", "reason": "no" })).await, Status::Forbidden, "an admin demoted an admin");
+    assert_eq!(put(&registry, promote, body).await, Status::Ok);
+
+    let promoted = db.user().find_unique(user::id::equals(target.id)).exec().await.unwrap().unwrap();
+    assert_eq!(promoted.role, Role::Moderator);
+    let actions = db.moderation_action()
+        .find_many(vec![moderation_action::actor_id::equals(actor)])
+        .exec().await.unwrap();
+    assert_eq!(actions.len(), 1, "only the successful change is recorded: {:?}", actions);
+    assert_eq!(actions[0].action, "set_role:Moderator");
+    assert_eq!(actions[0].reason, "helps out");
+
+    db.moderation_action().delete_many(vec![moderation_action::actor_id::equals(actor)]).exec().await.ok();
+    db.user().delete_many(vec![user::id::in_vec(vec![target.id, other_admin.id])]).exec().await.ok();
+    registry.cleanup().await;
+}
+
+#[rocket::async_test]
+async fn a_ban_ends_sessions_and_blocks_login() {
+    let (moderator, banned) = match (Registry::start().await, Registry::start().await) {
+        (Some(moderator), Some(banned)) => (moderator, banned),
+        _ => return
+    };
+    moderator.login().await;
+    banned.login().await;
+    let db = moderator.db().await;
+    let actor = moderator.user_id().await;
+    let target = banned.user_id().await;
+    set_role(&db, actor, Role::Moderator).await;
+
+    let ban = format!("/api/admin/users/{target}/ban");
+    assert_eq!(post(&moderator, ban, json!({ "reason": "malware" })).await, Status::Ok);
+    let user = banned.client.get("/api/user").dispatch().await;
+    assert_eq!(user.status(), Status::Unauthorized, "the banned user's session still works");
+    assert_eq!(banned.try_login().await, Status::Forbidden, "the banned user logged in again");
+
+    let unban = format!("/api/admin/users/{target}/unban");
+    assert_eq!(post(&moderator, unban, json!({ "reason": "appealed" })).await, Status::Ok);
+    banned.login().await;
+
+    // The user's data export shows what was done to them and the new session.
+    let export: Value = banned.client.get("/api/user/export").dispatch().await.into_json().await.expect("the export isn't JSON");
+    let received: Vec<_> = export["moderation_received"].as_array().expect("no moderation_received").iter()
+        .map(|action| action["action"].as_str().unwrap_or_default())
+        .collect();
+    assert_eq!(received, ["ban", "unban"]);
+    let sessions = export["sessions"].as_array().expect("no sessions");
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0]["current"], true);
+
+    db.moderation_action().delete_many(vec![moderation_action::actor_id::equals(actor)]).exec().await.ok();
+    moderator.cleanup().await;
+    banned.cleanup().await;
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use ed25519_dalek::{ExpandedSecretKey, PublicKey, SecretKey};
+use rocket::figment::Figment;
+use rocket::http::Status;
+use rocket::serde::json::{json, Value};
+use common::{same_origin, Registry};
+
+#[test]
+fn every_api_route_is_documented() {
+    let rocket = server::build(Figment::from(rocket::Config::default()));
+    let document = server::openapi::document(rocket.routes());
+    let mut undocumented = vec![];
+    for (path, operations) in document["paths"].as_object().expect("the document has no paths") {
+        for (method, operation) in operations.as_object().expect("a path has no operations") {
+            if operation.get("summary").is_none() {
+                undocumented.push(format!("{} {}", method.to_uppercase(), path));
+            }
+        }
+    }
+    assert!(undocumented.is_empty(), "missing from openapi::operations(): {:?}", undocumented);
+    let api_routes = rocket.routes().filter(|route| route.uri.path().starts_with("/api/")).count();
+    let documented: usize = document["paths"].as_object().unwrap().values()
+        .map(|operations| operations.as_object().unwrap().len())
+        .sum();
+    assert_eq!(documented, api_routes, "two routes share a method and path");
+}
+
+/// Where `value` doesn't match `schema`, resolving references in `document`.
+/// Properties the schema doesn't declare are reported unless they're null.
+fn mismatches(document: &Value, schema: &Value, value: &Value, at: &str, found: &mut Vec<String>) {
+    if let Some(reference) = schema["$ref"].as_str() {
+        let name = reference.trim_start_matches("#/components/schemas/");
+        let schema = &document["components"]["schemas"][name];
+        assert!(!schema.is_null(), "{at}: no schema {name}");
+        return mismatches(document, schema, value, at, found);
+    }
+    if let Some(all) = schema["allOf"].as_array() {
+        for schema in all {
+            mismatches(document, schema, value, at, found);
+        }
+        return;
+    }
+    if value.is_null() {
+        if schema["nullable"] != true {
+            found.push(format!("{at} is null"));
+        }
+        return;
+    }
+    let matches_type = match schema["type"].as_str() {
+        Some("string") => value.is_string(),
+        Some("integer") => value.is_i64() || value.is_u64(),
+        Some("boolean") => value.is_boolean(),
+        Some("array") => value.is_array(),
+        Some("object") => value.is_object(),
+        _ => true
+    };
+    if !matches_type {
+        found.push(format!("{at} should be {}, is {value}", schema["type"]));
+        return;
+    }
+    if let Some(options) = schema["enum"].as_array() {
+        if !options.contains(value) {
+            found.push(format!("{at}: {value} isn't one of {}", schema["enum"]));
+        }
+    }
+    if schema["format"] == "date-time" {
+        let text = value.as_str().unwrap_or_default();
+        if text.len() < 20 || text.as_bytes()[4] != b'-' || text.as_bytes()[10] != b'T' {
+            found.push(format!("{at}: {value} isn't an RFC 3339 date"));
+        }
+    }
+    if let Some(items) = value.as_array() {
+        for (index, item) in items.iter().enumerate() {
+            mismatches(document, &schema["items"], item, &format!("{at}[{index}]"), found);
+        }
+    }
+    if let Some(fields) = value.as_object() {
+        for required in schema["required"].as_array().into_iter().flatten() {
+            if !fields.contains_key(required.as_str().unwrap_or_default()) {
+                found.push(format!("{at}.{} is missing", required.as_str().unwrap_or_default()));
+            }
+        }
+        for (name, field) in fields {
+            match schema["properties"].get(name) {
+                Some(property) => mismatches(document, property, field, &format!("{at}.{name}"), found),
+                None if !field.is_null() => found.push(format!("{at}.{name} isn't documented")),
+                None => {}
+            }
+        }
+    }
+}
+
+/// Checks `value` against the schema `name`.
+fn assert_matches(document: &Value, name: &str, value: &Value) {
+    let mut found = vec![];
+    mismatches(document, &json!({ "$ref": format!("#/components/schemas/{name}") }), value, name, &mut found);
+    assert!(found.is_empty(), "{value} doesn't match the {name} schema: {found:#?}");
+}
+
+/// A base64 ed25519 public key and its proof for `POST /api/user/keys`.
+fn new_key() -> Value {
+    let secret = SecretKey::from_bytes(&rand::random::<[u8; 32]>()).unwrap();
+    let public = PublicKey::from(&secret);
+    let proof = ExpandedSecretKey::from(&secret).sign(public.as_bytes(), &public);
+    json!({ "public_key": base64::encode(public.as_bytes()), "proof": base64::encode(proof.to_bytes()) })
+}
+
+#[rocket::async_test]
+async fn responses_match_their_schemas() {
+    let registry = match Registry::start().await {
+        Some(registry) => registry,
+        None => return
+    };
+    let document: Value = registry.client.get("/api/openapi.json").dispatch().await.into_json().await.expect("the document isn't JSON");
+    let not_logged_in: Value = registry.client.get("/api/user").dispatch().await.into_json().await.expect("the error isn't JSON");
+    assert_matches(&document, "Error", &not_logged_in);
+    registry.login().await;
+
+    let user: Value = registry.client.get("/api/user").dispatch().await.into_json().await.unwrap();
+    assert_matches(&document, "User", &user);
+    let [host, origin] = same_origin();
+    let key = registry.client.post("/api/user/keys").header(host).header(origin).json(&new_key()).dispatch().await;
+    assert_eq!(key.status(), Status::Ok);
+    let key: Value = key.into_json().await.unwrap();
+    assert_matches(&document, "KeyInfo", &key);
+    let export: Value = registry.client.get("/api/user/export").dispatch().await.into_json().await.unwrap();
+    assert_eq!(export["signing_keys"].as_array().map(Vec::len), Some(1));
+    assert_matches(&document, "PersonalData", &export);
+    let forbidden: Value = registry.client.get("/api/admin/reports").dispatch().await.into_json().await.unwrap();
+    assert_matches(&document, "Error", &forbidden);
+    registry.cleanup().await;
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use rocket::http::Status;
+use rocket::serde::json::{json, Value};
+use common::{same_origin, Registry};
+use server::db::prisma::{moderation_action, report, user, Role};
+
+/// Reports in `list` with the id `id`.
+fn find(list: &Value, id: &Value) -> Option<Value> {
+    list.as_array().expect("not a list").iter().find(|report| &report["id"] == id).cloned()
+}
+
+#[rocket::async_test]
+async fn moderators_triage_reports_without_showing_notes_to_reporters() {
+    let (reporter, moderator) = match (Registry::start().await, Registry::start().await) {
+        (Some(reporter), Some(moderator)) => (reporter, moderator),
+        _ => return
+    };
+    reporter.login().await;
+    moderator.login().await;
+    let db = moderator.db().await;
+    let actor = moderator.user_id().await;
+    db.user().update(user::id::equals(actor), vec![user::role::set(Role::Moderator)]).exec().await.unwrap();
+    let [host, origin] = same_origin();
+
+    let filed = reporter.client.post("/api/reports")
+        .header(host.clone()).header(origin.clone())
+        .json(&json!({ "plugin": "reported-plugin", "category": "Malware", "description": "Deletes files" }))
+        .dispatch().await;
+    assert_eq!(filed.status(), Status::Ok);
+    let filed: Value = filed.into_json().await.unwrap();
+    assert_eq!(filed["status"], "Open");
+    let id = filed["id"].clone();
+
+    let queue = reporter.client.get("/api/admin/reports").dispatch().await;
+    assert_eq!(queue.status(), Status::Forbidden, "a user saw the queue");
+    let queue: Value = moderator.client.get("/api/admin/reports").dispatch().await.into_json().await.unwrap();
+    let queued = find(&queue, &id).expect("the new report isn't in the queue");
+    assert_eq!(queued["description"], "Deletes files");
+
+    let triage = moderator.client.put(format!("/api/admin/reports/{id}"))
+        .header(host).header(origin)
+        .json(&json!({ "status": "Triaged", "note": "Confirmed, contacting the author" }))
+        .dispatch().await;
+    assert_eq!(triage.status(), Status::Ok);
+
+    let queue: Value = moderator.client.get("/api/admin/reports").dispatch().await.into_json().await.unwrap();
+    assert!(find(&queue, &id).is_none(), "a triaged report is still open");
+    let triaged: Value = moderator.client.get("/api/admin/reports?status=Triaged").dispatch().await.into_json().await.unwrap();
+    let triaged = find(&triaged, &id).expect("the report isn't listed as triaged");
+    assert_eq!(triaged["moderator_note"], "Confirmed, contacting the author");
+
+    let own: Value = reporter.client.get("/api/reports/mine").dispatch().await.into_json().await.unwrap();
+    let own = find(&own, &id).expect("the reporter can't see their report");
+    assert_eq!(own["status"], "Triaged");
+    assert!(own.get("moderator_note").is_none(), "the reporter saw the moderator note: {own}");
+    assert!(own.get("handled_by").is_none(), "the reporter saw who handled the report: {own}");
+
+    let actions = db.moderation_action()
+        .find_many(vec![moderation_action::actor_id::equals(actor)])
+        .exec().await.unwrap();
+    assert_eq!(actions.len(), 1);
+    assert_eq!(actions[0].target, format!("report:{id}"));
+
+    db.moderation_action().delete_many(vec![moderation_action::actor_id::equals(actor)]).exec().await.ok();
+    db.report().delete(report::id::equals(id.as_i64().unwrap() as i32)).exec().await.ok();
+    reporter.cleanup().await;
+    moderator.cleanup().await;
+}